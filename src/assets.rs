@@ -0,0 +1,229 @@
+use crate::error::{create_dir, read_bytes, read_json, write_file, Error};
+use rayon::prelude::*;
+use sonic_rs::{JsonValueTrait, Value};
+use std::{
+    ffi::OsStr,
+    fs::read_dir,
+    path::{Path, PathBuf},
+};
+
+/// Header, that every encrypted asset of RPG Maker MV and MZ starts with.
+const HEADER: [u8; 16] = [
+    0x52, 0x50, 0x47, 0x4D, 0x56, 0x00, 0x00, 0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+/// Folders of the game, that contain assets.
+const ASSET_DIRS: [&str; 2] = ["img", "audio"];
+
+/// Extensions of encrypted assets of RPG Maker MV and MZ, and the extension of the decrypted asset.
+const EXTENSIONS: [(&str, &str, &str); 3] = [
+    ("rpgmvp", "png_", "png"),
+    ("rpgmvo", "ogg_", "ogg"),
+    ("rpgmvm", "m4a_", "m4a"),
+];
+
+/// Reads the encryption key of the assets from System.json file of the game.
+pub fn encryption_key(system_file_path: &Path) -> Result<[u8; 16], Error> {
+    let system_obj: Value = read_json(system_file_path)?;
+    let key_string: &str = system_obj["encryptionKey"].as_str().unwrap_or_default();
+    let mut key: [u8; 16] = [0; 16];
+
+    if key_string.len() != 32 || !key_string.is_ascii() {
+        return Err(Error::MissingEncryptionKey(system_file_path.to_path_buf()));
+    }
+
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key_string[i * 2..i * 2 + 2], 16)
+            .map_err(|_| Error::MissingEncryptionKey(system_file_path.to_path_buf()))?;
+    }
+
+    Ok(key)
+}
+
+/// Decrypts the content of the encrypted asset. Only the first 16 bytes after the header are encrypted, by XOR with
+/// the key. Returns None, if the content doesn't start with the header.
+pub fn decrypt(content: &[u8], key: &[u8; 16]) -> Option<Vec<u8>> {
+    let mut decrypted: Vec<u8> = content.strip_prefix(&HEADER)?.to_vec();
+
+    for (byte, key_byte) in decrypted.iter_mut().zip(key) {
+        *byte ^= key_byte;
+    }
+
+    Some(decrypted)
+}
+
+/// Encrypts the content of the asset, so the game can decrypt it with the key.
+pub fn encrypt(content: &[u8], key: &[u8; 16]) -> Vec<u8> {
+    let mut encrypted: Vec<u8> = Vec::with_capacity(HEADER.len() + content.len());
+    encrypted.extend_from_slice(&HEADER);
+    encrypted.extend_from_slice(content);
+
+    for (byte, key_byte) in encrypted[HEADER.len()..].iter_mut().zip(key) {
+        *byte ^= key_byte;
+    }
+
+    encrypted
+}
+
+/// Pushes paths of all files of the directory and of its subdirectories to files.
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = read_dir(path) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let entry_path: PathBuf = entry.path();
+
+        if entry_path.is_dir() {
+            collect_files(&entry_path, files);
+        } else {
+            files.push(entry_path);
+        }
+    }
+}
+
+/// Returns the extension of the file, if it's valid UTF-8.
+fn extension(path: &Path) -> &str {
+    path.extension()
+        .and_then(|extension: &OsStr| extension.to_str())
+        .unwrap_or_default()
+}
+
+/// Decrypts encrypted assets of `img` and `audio` folders of the game to the assets directory, preserving their
+/// relative paths.
+/// # Parameters
+/// * `game_path` - path to the directory, that contains `img` and `audio` folders of the game
+/// * `assets_path` - path to the directory, where decrypted assets are written
+/// * `key` - encryption key of the assets
+/// * `logging` - whether to log or not
+/// * `file_decrypted_msg` - message to log when file is decrypted
+pub fn decrypt_assets(
+    game_path: &Path,
+    assets_path: &Path,
+    key: &[u8; 16],
+    logging: bool,
+    file_decrypted_msg: &str,
+) -> Vec<Error> {
+    let mut files: Vec<PathBuf> = Vec::new();
+
+    for dir in ASSET_DIRS {
+        collect_files(&game_path.join(dir), &mut files);
+    }
+
+    files
+        .into_par_iter()
+        .filter_map(|path: PathBuf| {
+            let (_, _, decrypted_extension) = EXTENSIONS
+                .into_iter()
+                .find(|(mv_extension, mz_extension, _)| [*mv_extension, *mz_extension].contains(&extension(&path)))?;
+
+            let relative_path: &Path = path.strip_prefix(game_path).ok()?;
+            let decrypted_file_path: PathBuf = assets_path.join(relative_path).with_extension(decrypted_extension);
+
+            let result: Result<(), Error> = (|| {
+                let content: Vec<u8> =
+                    decrypt(&read_bytes(&path)?, key).ok_or_else(|| Error::InvalidEncryptedFile(path.clone()))?;
+
+                create_dir(decrypted_file_path.parent().unwrap_or(assets_path))?;
+                write_file(&decrypted_file_path, content)
+            })();
+
+            if logging && result.is_ok() {
+                println!("{file_decrypted_msg} {}", relative_path.display());
+            }
+
+            result.err()
+        })
+        .collect()
+}
+
+/// Encrypts assets of the assets directory to the output directory, preserving their relative paths. Every asset gets
+/// the extension of its encrypted original, or, if the game doesn't have such asset, the extension used by the engine
+/// of the game.
+/// # Parameters
+/// * `assets_path` - path to the directory, that contains decrypted assets
+/// * `game_path` - path to the directory, that contains `img` and `audio` folders of the game
+/// * `output_path` - path to the directory, where encrypted assets are written
+/// * `key` - encryption key of the assets
+/// * `logging` - whether to log or not
+/// * `file_encrypted_msg` - message to log when file is encrypted
+pub fn encrypt_assets(
+    assets_path: &Path,
+    game_path: &Path,
+    output_path: &Path,
+    key: &[u8; 16],
+    logging: bool,
+    file_encrypted_msg: &str,
+) -> Vec<Error> {
+    let is_mz: bool = game_path.join("js/rmmz_core.js").exists();
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_files(assets_path, &mut files);
+
+    files
+        .into_par_iter()
+        .filter_map(|path: PathBuf| {
+            let (mv_extension, mz_extension, _) = EXTENSIONS
+                .into_iter()
+                .find(|(_, _, decrypted_extension)| *decrypted_extension == extension(&path))?;
+
+            let relative_path: &Path = path.strip_prefix(assets_path).ok()?;
+
+            let encrypted_extension: &str = if game_path.join(relative_path).with_extension(mv_extension).exists() {
+                mv_extension
+            } else if game_path.join(relative_path).with_extension(mz_extension).exists() || is_mz {
+                mz_extension
+            } else {
+                mv_extension
+            };
+
+            let encrypted_file_path: PathBuf = output_path.join(relative_path).with_extension(encrypted_extension);
+
+            let result: Result<(), Error> = (|| {
+                let content: Vec<u8> = encrypt(&read_bytes(&path)?, key);
+
+                create_dir(encrypted_file_path.parent().unwrap_or(output_path))?;
+                write_file(&encrypted_file_path, content)
+            })();
+
+            if logging && result.is_ok() {
+                println!("{file_encrypted_msg} {}", relative_path.display());
+            }
+
+            result.err()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; 16] = [
+        0xd4, 0x1d, 0x8c, 0xd9, 0x8f, 0x00, 0xb2, 0x04, 0xe9, 0x80, 0x09, 0x98, 0xec, 0xf8, 0x42, 0x7e,
+    ];
+
+    #[test]
+    fn encrypted_asset_is_decrypted_back() {
+        let content: Vec<u8> = (0..64).collect();
+        let encrypted: Vec<u8> = encrypt(&content, &KEY);
+
+        assert_eq!(&encrypted[..HEADER.len()], HEADER);
+        assert_ne!(&encrypted[HEADER.len()..HEADER.len() + 16], &content[..16]);
+        // Only the first 16 bytes of the content are encrypted
+        assert_eq!(&encrypted[HEADER.len() + 16..], &content[16..]);
+
+        assert_eq!(decrypt(&encrypted, &KEY), Some(content));
+    }
+
+    #[test]
+    fn short_assets_are_encrypted() {
+        let content: [u8; 3] = [1, 2, 3];
+        assert_eq!(decrypt(&encrypt(&content, &KEY), &KEY).as_deref(), Some(&content[..]));
+    }
+
+    #[test]
+    fn content_without_header_is_not_decrypted() {
+        assert_eq!(decrypt(&[0; 32], &KEY), None);
+        assert_eq!(decrypt(&HEADER[..8], &KEY), None);
+    }
+}
@@ -0,0 +1,207 @@
+#![allow(clippy::too_many_arguments)]
+use crate::{
+    error::{read_file, Error},
+    placeholders::{placeholders_match, ESCAPE_CODE_RE},
+    po::{parse_po, PoEntry},
+    section_names, Format, CONTEXT_SEPARATOR, SECTION_DIRS,
+};
+use regex::Match;
+use std::path::Path;
+
+/// Returns escape codes of the text in sorted order, so the codes of original and translation can be compared
+/// regardless of their order. Letters of escape codes are case-insensitive.
+fn escape_codes(text: &str) -> Vec<String> {
+    let mut codes: Vec<String> = ESCAPE_CODE_RE
+        .find_iter(text)
+        .map(|code: Match| code.as_str().to_uppercase())
+        .collect();
+
+    codes.sort();
+    codes
+}
+
+// Returns whether any of the lines of the text, separated by \#, ends with whitespace
+fn has_trailing_whitespace(text: &str) -> bool {
+    text.split(r"\#").any(|line: &str| line != line.trim_end())
+}
+
+/// Checks the translation of a single line, returning the messages of all problems found.
+fn check_line<'a>(
+    original: &str,
+    translated: &str,
+    ignore_untranslated: bool,
+    escape_codes_mismatch_msg: &'a str,
+    line_breaks_mismatch_msg: &'a str,
+    trailing_whitespace_msg: &'a str,
+    untranslated_msg: &'a str,
+) -> Vec<&'a str> {
+    let mut problems: Vec<&str> = Vec::new();
+
+    // Empty translation of location-specific entry means that the translation of the line is used
+    let contextual: bool = original.contains(CONTEXT_SEPARATOR);
+    let original: &str = original.rsplit(CONTEXT_SEPARATOR).next().unwrap();
+
+    if translated.is_empty() {
+        if !ignore_untranslated && !contextual && !original.is_empty() {
+            problems.push(untranslated_msg);
+        }
+
+        return problems;
+    }
+
+    // Escape codes can be replaced with placeholders, if the text was read with them
+    if escape_codes(original) != escape_codes(translated) || !placeholders_match(original, translated) {
+        problems.push(escape_codes_mismatch_msg);
+    }
+
+    if original.matches(r"\#").count() != translated.matches(r"\#").count() {
+        problems.push(line_breaks_mismatch_msg);
+    }
+
+    if has_trailing_whitespace(translated) && !has_trailing_whitespace(original) {
+        problems.push(trailing_whitespace_msg);
+    }
+
+    problems
+}
+
+/// Checks translation files of maps, other and plugins folders for problems, that would break the translation when
+/// writing, and prints `file:line: problem` diagnostics for all of them. Returns the number of problems found, or the
+/// error of the translation file, that couldn't be read.
+/// # Parameters
+/// * `translation_path` - path to the translation directory
+/// * `format` - format of translation files
+/// * `ignore_untranslated` - whether to skip reporting untranslated lines
+/// * `lines_count_mismatch_msg` - message for translation file, which number of lines differs from the original file
+/// * `escape_codes_mismatch_msg` - message for translation, that lost or added escape codes of the original
+/// * `line_breaks_mismatch_msg` - message for translation, which number of line breaks differs from the original
+/// * `trailing_whitespace_msg` - message for translation with trailing whitespace, that the original doesn't have
+/// * `untranslated_msg` - message for untranslated line
+pub fn check(
+    translation_path: &Path,
+    format: Format,
+    ignore_untranslated: bool,
+    lines_count_mismatch_msg: &str,
+    escape_codes_mismatch_msg: &str,
+    line_breaks_mismatch_msg: &str,
+    trailing_whitespace_msg: &str,
+    untranslated_msg: &str,
+) -> Result<usize, Error> {
+    let mut problems_count: usize = 0;
+
+    for dir in SECTION_DIRS {
+        let path: &Path = &translation_path.join(dir);
+
+        if !path.exists() {
+            continue;
+        }
+
+        for name in section_names(path, format)? {
+            // Line number of the problem, the line of the original and its translation
+            let (file, lines): (String, Vec<(usize, String, String)>) = match format {
+                Format::Txt => {
+                    let file: String = format!("{dir}/{name}_trans.txt");
+
+                    let original_content: String = read_file(&path.join(format!("{name}.txt")))?;
+                    let translated_content: String = read_file(&path.join(format!("{name}_trans.txt")))?;
+                    let original_lines: Vec<&str> = original_content.split('\n').collect();
+                    let translated_lines: Vec<&str> = translated_content.split('\n').collect();
+
+                    // Lines are matched by their position, so all lines after the missing or extra one are shifted
+                    if original_lines.len() != translated_lines.len() {
+                        println!(
+                            "{file}:{}: {lines_count_mismatch_msg} ({} / {})",
+                            original_lines.len().min(translated_lines.len()) + 1,
+                            original_lines.len(),
+                            translated_lines.len()
+                        );
+                        problems_count += 1;
+                    }
+
+                    let lines: Vec<(usize, String, String)> = original_lines
+                        .into_iter()
+                        .zip(translated_lines)
+                        .enumerate()
+                        .map(|(i, (original, translated))| (i + 1, original.to_string(), translated.to_string()))
+                        .collect();
+
+                    (file, lines)
+                }
+                Format::Po => {
+                    let lines: Vec<(usize, String, String)> = parse_po(&read_file(&path.join(format!("{name}.po")))?)
+                        .into_iter()
+                        .map(|entry: PoEntry| (entry.line, entry.key(), entry.translated))
+                        .collect();
+
+                    (format!("{dir}/{name}.po"), lines)
+                }
+            };
+
+            for (line, original, translated) in lines {
+                for problem in check_line(
+                    &original,
+                    &translated,
+                    ignore_untranslated,
+                    escape_codes_mismatch_msg,
+                    line_breaks_mismatch_msg,
+                    trailing_whitespace_msg,
+                    untranslated_msg,
+                ) {
+                    println!("{file}:{line}: {problem}");
+                    problems_count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(problems_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(original: &str, translated: &str) -> Vec<&'static str> {
+        check_line(
+            original,
+            translated,
+            false,
+            "escape codes",
+            "line breaks",
+            "trailing whitespace",
+            "untranslated",
+        )
+    }
+
+    #[test]
+    fn matching_translation_has_no_problems() {
+        assert!(problems(r"\C[2]Hello\C[0]\#there", r"\c[2]Привет\C[0]\#всем").is_empty());
+        assert!(problems(r"\C[2]Hello\C[0]", r"\C[0]Привет\C[2]").is_empty());
+        assert!(problems("{C2}Hello{WAIT}", "{WAIT}Привет{C2}").is_empty());
+        assert!(problems("Hello ", "Привет ").is_empty());
+    }
+
+    #[test]
+    fn mismatches_are_reported() {
+        assert_eq!(problems(r"\C[2]Hello", "Привет"), ["escape codes"]);
+        assert_eq!(problems("{C2}Hello", "Привет"), ["escape codes"]);
+        assert_eq!(problems(r"Hello\#there", "Привет всем"), ["line breaks"]);
+        assert_eq!(problems(r"Hello\#there", r"Привет \#всем"), ["trailing whitespace"]);
+        assert_eq!(
+            problems(r"\N[1]\#Hello", "Привет  "),
+            ["escape codes", "line breaks", "trailing whitespace"]
+        );
+    }
+
+    #[test]
+    fn untranslated_lines_are_reported_unless_ignored() {
+        assert_eq!(problems("Hello", ""), ["untranslated"]);
+        assert!(problems("", "").is_empty());
+
+        // Empty translation of location-specific entry falls back to the translation of the line
+        assert!(problems(&format!("Map001.json:1:0:2{CONTEXT_SEPARATOR}Hello"), "").is_empty());
+
+        let ignored: Vec<&str> = check_line("Hello", "", true, "", "", "", "untranslated");
+        assert!(ignored.is_empty());
+    }
+}
@@ -0,0 +1,434 @@
+use crate::Code;
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use std::ops::Range;
+
+/// Describes where the text is stored in the parameters of event command.
+pub enum Extraction {
+    /// Parameter is a line, that is joined with the parameters of the following commands with continuation code into
+    /// a single text, e.g. dialogue box, which lines are stored in separate 401 commands.
+    Sequence { parameter: usize, continuation: u64 },
+    /// Parameter is an array of separate texts, e.g. choices of 102 command.
+    Array(usize),
+    /// Parameter is a single text.
+    Parameter(usize),
+    /// String literals of the script in the first parameter are separate texts.
+    Script,
+    /// String arguments of MZ plugin command in the fourth parameter are separate texts.
+    PluginArguments,
+    /// First parameter is the `name = value` representation of MZ plugin command argument, that is displayed in the
+    /// editor. Its value is translated when writing, if it was parsed from the plugin command.
+    PluginArgumentText,
+}
+
+/// Describes how text of the event command with the code is extracted. This table is shared by reading and writing,
+/// so both of them always process the same commands in the same way.
+pub struct CodeRule {
+    pub code: u64,
+    pub kind: Code,
+    pub extraction: Extraction,
+    /// Whether text of the command is parsed. Text of some commands duplicates the text of another command, e.g. 402
+    /// holds one of the choices of 102, so it's not parsed, but still written with translation of that text.
+    pub parsed: bool,
+    pub enabled_by_default: bool,
+}
+
+// 105 - scrolling text header, that holds no text itself, while its lines are stored in 405 commands.
+// Comments and scripts are disabled by default, since most of them are not displayed in-game, and translation of
+// strings in scripts may break the game, if they're compared with something.
+pub const CODE_RULES: [CodeRule; 15] = [
+    // Speaker name of dialogue, only present in MZ
+    CodeRule {
+        code: 101,
+        kind: Code::Speaker,
+        extraction: Extraction::Parameter(4),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Dialogue lines
+    CodeRule {
+        code: 401,
+        kind: Code::Dialogue,
+        extraction: Extraction::Sequence {
+            parameter: 0,
+            continuation: 401,
+        },
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Scrolling text lines
+    CodeRule {
+        code: 405,
+        kind: Code::Dialogue,
+        extraction: Extraction::Sequence {
+            parameter: 0,
+            continuation: 405,
+        },
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Dialogue choices array
+    CodeRule {
+        code: 102,
+        kind: Code::Choice,
+        extraction: Extraction::Array(0),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // One of the dialogue choices from the array
+    CodeRule {
+        code: 402,
+        kind: Code::Choice,
+        extraction: Extraction::Parameter(1),
+        parsed: false,
+        enabled_by_default: true,
+    },
+    // Comment, which lines are used by some message plugins
+    CodeRule {
+        code: 108,
+        kind: Code::Dialogue,
+        extraction: Extraction::Sequence {
+            parameter: 0,
+            continuation: 408,
+        },
+        parsed: true,
+        enabled_by_default: false,
+    },
+    // Comment continuation lines
+    CodeRule {
+        code: 408,
+        kind: Code::Dialogue,
+        extraction: Extraction::Sequence {
+            parameter: 0,
+            continuation: 408,
+        },
+        parsed: true,
+        enabled_by_default: false,
+    },
+    // Change name
+    CodeRule {
+        code: 320,
+        kind: Code::Unknown,
+        extraction: Extraction::Parameter(1),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Change nickname
+    CodeRule {
+        code: 324,
+        kind: Code::Unknown,
+        extraction: Extraction::Parameter(1),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Change profile
+    CodeRule {
+        code: 325,
+        kind: Code::Unknown,
+        extraction: Extraction::Parameter(1),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // System lines (special texts), plugin command in MV
+    CodeRule {
+        code: 356,
+        kind: Code::System,
+        extraction: Extraction::Parameter(0),
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Script
+    CodeRule {
+        code: 355,
+        kind: Code::Unknown,
+        extraction: Extraction::Script,
+        parsed: true,
+        enabled_by_default: false,
+    },
+    // Script continuation lines
+    CodeRule {
+        code: 655,
+        kind: Code::Unknown,
+        extraction: Extraction::Script,
+        parsed: true,
+        enabled_by_default: false,
+    },
+    // Plugin command in MZ
+    CodeRule {
+        code: 357,
+        kind: Code::Unknown,
+        extraction: Extraction::PluginArguments,
+        parsed: true,
+        enabled_by_default: true,
+    },
+    // Plugin command arguments, displayed in the editor
+    CodeRule {
+        code: 657,
+        kind: Code::Unknown,
+        extraction: Extraction::PluginArgumentText,
+        parsed: false,
+        enabled_by_default: true,
+    },
+];
+
+lazy_static! {
+    static ref SCRIPT_STRING_LITERAL_RE: Regex = Regex::new(r#""((?:[^"\\\n]|\\.)*)"|'((?:[^'\\\n]|\\.)*)'"#).unwrap();
+}
+
+/// Returns the rule of the event command code, if text of such commands is extracted.
+pub fn get_code_rule(code: u64) -> Option<&'static CodeRule> {
+    CODE_RULES.iter().find(|rule: &&CodeRule| rule.code == code)
+}
+
+/// Returns codes of the event commands, which text is parsed, out of the enabled codes.
+pub fn parsed_codes(codes: &[u64]) -> Vec<u64> {
+    CODE_RULES
+        .iter()
+        .filter(|rule: &&CodeRule| rule.parsed && codes.contains(&rule.code))
+        .map(|rule: &CodeRule| rule.code)
+        .collect()
+}
+
+/// Returns codes of the event commands, which text is written, out of the enabled codes.
+pub fn written_codes(codes: &[u64]) -> Vec<u64> {
+    CODE_RULES
+        .iter()
+        .filter(|rule: &&CodeRule| codes.contains(&rule.code))
+        .map(|rule: &CodeRule| rule.code)
+        .collect()
+}
+
+/// Returns codes of the event commands, which text is extracted by default.
+pub fn default_codes() -> Vec<u64> {
+    CODE_RULES
+        .iter()
+        .filter(|rule: &&CodeRule| rule.enabled_by_default)
+        .map(|rule: &CodeRule| rule.code)
+        .collect()
+}
+
+/// Joins the lines of a sequence of commands to the text, by which the sequence is keyed in translation files. Empty
+/// lines are skipped, and the rest are trimmed, so the same text is joined when reading and writing.
+pub fn join_sequence_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> String {
+    lines
+        .into_iter()
+        .filter(|line: &&str| !line.is_empty())
+        .map(str::trim)
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Returns contents of string literals of the script with their byte ranges in the script.
+pub fn script_string_literals(script: &str) -> Vec<(Range<usize>, &str)> {
+    SCRIPT_STRING_LITERAL_RE
+        .captures_iter(script)
+        .filter_map(|captures: Captures| captures.get(1).or_else(|| captures.get(2)))
+        .map(|content| (content.range(), content.as_str()))
+        .collect()
+}
+
+/// Replaces the content of the string literal at range in the script, escaping the quotes of the literal.
+pub fn replace_script_string_literal(script: &mut String, range: Range<usize>, replacement: &str) {
+    let quote: char = script[..range.start].chars().next_back().unwrap();
+    let mut escaped: String = String::with_capacity(replacement.len());
+    let mut previous_char: char = '\0';
+
+    for char in replacement.chars() {
+        if char == quote && previous_char != '\\' {
+            escaped.push('\\');
+        }
+
+        escaped.push(char);
+        previous_char = char;
+    }
+
+    script.replace_range(range, &escaped);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        project::Section, read::parse_list, rules::Rules, write::write_list, ProcessingMode, CONTEXT_SEPARATOR,
+    };
+    use indexmap::IndexMap;
+    use sonic_rs::{from_str, Array, JsonContainerTrait, JsonValueTrait};
+    use std::{collections::HashMap, hash::BuildHasherDefault};
+    use xxhash_rust::xxh3::Xxh3;
+
+    type Lines = IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>>;
+    type TranslationMap = HashMap<String, String, BuildHasherDefault<Xxh3>>;
+
+    const LIST: &str = r#"[
+        {"code": 101, "indent": 0, "parameters": ["Actor1", 0, 0, 2, "Guard"]},
+        {"code": 401, "indent": 0, "parameters": ["Hello there!"]},
+        {"code": 401, "indent": 0, "parameters": ["How are you?"]},
+        {"code": 102, "indent": 0, "parameters": [["Yes", "No"], 1, 0, 2, 0]},
+        {"code": 402, "indent": 0, "parameters": [0, "Yes"]},
+        {"code": 401, "indent": 1, "parameters": ["Good."]},
+        {"code": 0, "indent": 1, "parameters": []},
+        {"code": 402, "indent": 0, "parameters": [1, "No"]},
+        {"code": 401, "indent": 1, "parameters": ["Bad."]},
+        {"code": 0, "indent": 1, "parameters": []},
+        {"code": 404, "indent": 0, "parameters": []},
+        {"code": 105, "indent": 0, "parameters": [2, false]},
+        {"code": 405, "indent": 0, "parameters": ["Scrolling line"]},
+        {"code": 405, "indent": 0, "parameters": ["Another scrolling line"]},
+        {"code": 108, "indent": 0, "parameters": ["Comment"]},
+        {"code": 408, "indent": 0, "parameters": ["Comment continuation"]},
+        {"code": 320, "indent": 0, "parameters": [1, "Bob"]},
+        {"code": 324, "indent": 0, "parameters": [1, "The Brave"]},
+        {"code": 325, "indent": 0, "parameters": [1, "A wandering guard"]},
+        {"code": 356, "indent": 0, "parameters": ["ShowInfo hello"]},
+        {"code": 355, "indent": 0, "parameters": ["$gameMessage.add(\"Scripted line\");"]},
+        {"code": 655, "indent": 0, "parameters": ["$gameMessage.add('Scripted continuation');"]},
+        {"code": 357, "indent": 0, "parameters": ["TextPlugin", "show", "Show", {"message": "Plugin line"}]},
+        {"code": 657, "indent": 0, "parameters": ["Message = Plugin line"]},
+        {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2]},
+        {"code": 401, "indent": 0, "parameters": ["Line before an empty one"]},
+        {"code": 401, "indent": 0, "parameters": [""]},
+        {"code": 401, "indent": 0, "parameters": ["Line after an empty one"]},
+        {"code": 0, "indent": 0, "parameters": []}
+    ]"#;
+
+    fn all_codes() -> Vec<u64> {
+        CODE_RULES.iter().map(|rule: &CodeRule| rule.code).collect()
+    }
+
+    fn parse(list: &Array) -> (Lines, Lines) {
+        let mut lines: Lines = IndexMap::default();
+        let mut map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut speakers_lines: Lines = IndexMap::default();
+        let mut speakers_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+        parse_list(
+            list,
+            &parsed_codes(&all_codes()),
+            "Map001.json:1:0",
+            false,
+            false,
+            Section::Maps,
+            &None,
+            &Rules::default(),
+            &ProcessingMode::Default,
+            &mut lines,
+            &mut map,
+            &ProcessingMode::Default,
+            &mut speakers_lines,
+            &mut speakers_map,
+            None,
+        );
+
+        (lines, speakers_lines)
+    }
+
+    fn translate(lines: &Lines) -> TranslationMap {
+        lines
+            .keys()
+            .map(|line: &String| {
+                let original: String = line.replace(r"\#", "\n");
+                let translated: String = format!("T:{original}");
+                (original, translated)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn written_text_is_parsed_back() {
+        let mut list: Array = from_str(LIST).unwrap();
+        let (lines, speakers_lines) = parse(&list);
+
+        assert!(!lines.is_empty());
+        assert_eq!(speakers_lines.keys().collect::<Vec<&String>>(), ["Guard"]);
+
+        write_list(
+            &mut list,
+            &written_codes(&all_codes()),
+            "Map001.json:1:0",
+            false,
+            Section::Maps,
+            &None,
+            &Rules::default(),
+            &translate(&lines),
+            &translate(&speakers_lines),
+            false,
+            "",
+            None,
+            0,
+        );
+
+        // Every parsed line must be written, and written lines must be parsed at the same place as original ones
+        let (translated_lines, translated_speakers_lines) = parse(&list);
+
+        let expected_lines: Vec<String> = lines.keys().map(|line: &String| format!("T:{line}")).collect();
+        let expected_locations: Vec<&Vec<String>> = lines.values().collect();
+
+        assert_eq!(
+            translated_lines.keys().cloned().collect::<Vec<String>>(),
+            expected_lines
+        );
+        assert_eq!(
+            translated_lines.values().collect::<Vec<&Vec<String>>>(),
+            expected_locations
+        );
+        assert_eq!(translated_speakers_lines.keys().collect::<Vec<&String>>(), ["T:Guard"]);
+
+        // Commands, which text isn't parsed, must be written with the translation of the text they duplicate
+        for item in list.iter() {
+            let parameters: &Array = item["parameters"].as_array().unwrap();
+
+            match item["code"].as_u64().unwrap() {
+                402 => assert!(parameters[1].as_str().unwrap().starts_with("T:")),
+                657 => assert_eq!(parameters[0].as_str().unwrap(), "Message = T:Plugin line"),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn contextual_translation_is_written_at_the_first_non_empty_line() {
+        let mut list: Array = from_str(
+            r#"[
+                {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2]},
+                {"code": 401, "indent": 0, "parameters": [""]},
+                {"code": 401, "indent": 0, "parameters": ["Hello"]},
+                {"code": 0, "indent": 0, "parameters": []},
+                {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2]},
+                {"code": 401, "indent": 0, "parameters": ["Hello"]},
+                {"code": 0, "indent": 0, "parameters": []}
+            ]"#,
+        )
+        .unwrap();
+        let (lines, _) = parse(&list);
+
+        assert_eq!(lines["Hello"], ["Map001.json:1:0:2", "Map001.json:1:0:5"]);
+
+        let mut map: TranslationMap = translate(&lines);
+        map.insert(
+            format!("Map001.json:1:0:2{CONTEXT_SEPARATOR}Hello"),
+            "Contextual".into(),
+        );
+
+        write_list(
+            &mut list,
+            &written_codes(&all_codes()),
+            "Map001.json:1:0",
+            false,
+            Section::Maps,
+            &None,
+            &Rules::default(),
+            &map,
+            &TranslationMap::default(),
+            false,
+            "",
+            None,
+            0,
+        );
+
+        assert_eq!(list[1]["parameters"][0].as_str(), Some(""));
+        assert_eq!(list[2]["parameters"][0].as_str(), Some("Contextual"));
+        assert_eq!(list[5]["parameters"][0].as_str(), Some("T:Hello"));
+    }
+}
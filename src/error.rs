@@ -0,0 +1,127 @@
+use sonic_rs::{from_str, Deserialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    fs::{create_dir_all, read, read_dir, read_to_string, remove_dir_all, write, ReadDir},
+    io,
+    path::{Path, PathBuf},
+};
+
+/// Error, that prevents reading or writing of a single file, or of a whole section, if the file is shared by the files
+/// of the section.
+#[derive(Debug)]
+pub enum Error {
+    /// Directory, that is required for the operation, doesn't exist.
+    MissingDirectory(PathBuf),
+    /// File, that is specified explicitly, doesn't exist.
+    MissingFile(PathBuf),
+    /// File couldn't be read or written.
+    Io(PathBuf, io::Error),
+    /// Game file isn't valid JSON.
+    Json(PathBuf, sonic_rs::Error),
+    /// Translation file doesn't exist, while the other file of its section does.
+    MissingTranslationFile(PathBuf),
+    /// Number of lines of the translation file differs from the number of lines of the original text.
+    LinesCountMismatch {
+        path: PathBuf,
+        original: usize,
+        translated: usize,
+    },
+    /// System.json doesn't contain a valid encryption key of the assets.
+    MissingEncryptionKey(PathBuf),
+    /// Asset doesn't start with the header of RPG Maker encrypted files.
+    InvalidEncryptedFile(PathBuf),
+    /// Game profile doesn't follow the format of profiles.
+    InvalidProfile(PathBuf, String),
+    /// There's no built-in game profile with the id.
+    UnknownGame(String),
+    /// Rules file doesn't follow the format of rules.
+    InvalidRules(PathBuf, String),
+    /// File is valid JSON or XML, but doesn't have the structure of the file of its kind.
+    InvalidFile(PathBuf, String),
+    /// XLIFF file isn't valid XML.
+    Xml(PathBuf, roxmltree::Error),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Error::MissingDirectory(path) => write!(f, "{}: directory doesn't exist", path.display()),
+            Error::MissingFile(path) => write!(f, "{}: file doesn't exist", path.display()),
+            Error::Io(path, error) => write!(f, "{}: {error}", path.display()),
+            Error::Json(path, error) => write!(f, "{}: {error}", path.display()),
+            Error::MissingTranslationFile(path) => write!(f, "{}: translation file doesn't exist", path.display()),
+            Error::LinesCountMismatch {
+                path,
+                original,
+                translated,
+            } => write!(
+                f,
+                "{}: translation has {translated} lines, while the original has {original}",
+                path.display()
+            ),
+            Error::MissingEncryptionKey(path) => write!(f, "{}: encryption key is missing or invalid", path.display()),
+            Error::InvalidEncryptedFile(path) => write!(f, "{}: file isn't an encrypted asset", path.display()),
+            Error::InvalidProfile(path, message) => write!(f, "{}: invalid game profile: {message}", path.display()),
+            Error::UnknownGame(id) => write!(f, "{id}: there's no built-in game profile with this id"),
+            Error::InvalidRules(path, message) => write!(f, "{}: invalid rules: {message}", path.display()),
+            Error::InvalidFile(path, message) => write!(f, "{}: unexpected structure: {message}", path.display()),
+            Error::Xml(path, error) => write!(f, "{}: {error}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(_, error) => Some(error),
+            Error::Json(_, error) => Some(error),
+            Error::Xml(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the file to a string, returning the error with the path of the file.
+pub fn read_file(path: &Path) -> Result<String, Error> {
+    read_to_string(path).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Writes the content to the file, returning the error with the path of the file.
+pub fn write_file<C: AsRef<[u8]>>(path: &Path, content: C) -> Result<(), Error> {
+    write(path, content).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Parses JSON content of the file, returning the error with the path of the file.
+pub fn parse_json<'a, T: Deserialize<'a>>(path: &Path, content: &'a str) -> Result<T, Error> {
+    from_str(content).map_err(|error: sonic_rs::Error| Error::Json(path.to_path_buf(), error))
+}
+
+/// Reads the file to bytes, returning the error with the path of the file.
+pub fn read_bytes(path: &Path) -> Result<Vec<u8>, Error> {
+    read(path).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Creates the directory with all its parents, returning the error with the path of the directory.
+pub fn create_dir(path: &Path) -> Result<(), Error> {
+    create_dir_all(path).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Reads entries of the directory, returning the error with the path of the directory.
+pub fn read_directory(path: &Path) -> Result<ReadDir, Error> {
+    read_dir(path).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Removes the directory with all its content, returning the error with the path of the directory.
+pub fn remove_dir(path: &Path) -> Result<(), Error> {
+    remove_dir_all(path).map_err(|error: io::Error| Error::Io(path.to_path_buf(), error))
+}
+
+/// Returns the error of the game file, which value of the key is missing or has the wrong type.
+pub fn invalid_value(path: &Path, key: &str) -> Error {
+    Error::InvalidFile(path.to_path_buf(), format!("`{key}` is missing or has the wrong type"))
+}
+
+/// Reads and parses JSON file, returning the error with the path of the file.
+pub fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, Error> {
+    parse_json(path, &read_file(path)?)
+}
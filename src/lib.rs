@@ -0,0 +1,512 @@
+#![allow(clippy::too_many_arguments)]
+use error::{read_directory, read_json, Error};
+use lazy_static::lazy_static;
+use profile::{GameInfo, GameProfile};
+use rayon::prelude::*;
+use regex::Regex;
+use sonic_rs::{from_str, Deserialize, JsonContainerTrait, JsonValueTrait, Object, Value};
+use std::{
+    collections::HashMap,
+    fs::{read_dir, read_to_string, DirEntry, ReadDir},
+    path::{Path, PathBuf},
+};
+
+pub mod assets;
+pub mod check;
+pub mod codes;
+pub mod error;
+pub mod placeholders;
+pub mod po;
+pub mod profile;
+pub mod project;
+pub mod read;
+pub mod rules;
+pub mod stats;
+pub mod verify;
+pub mod wrap;
+pub mod write;
+pub mod xliff;
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum ProcessingMode {
+    Force,
+    Append,
+    Default,
+}
+
+impl AsRef<ProcessingMode> for ProcessingMode {
+    fn as_ref(&self) -> &ProcessingMode {
+        self
+    }
+}
+
+impl PartialEq<ProcessingMode> for &ProcessingMode {
+    fn eq(&self, other: &ProcessingMode) -> bool {
+        *self == other
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Format {
+    Txt,
+    Po,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Code {
+    Speaker,
+    Dialogue, // also goes for credit
+    Choice,
+    System,
+    Unknown,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum Variable {
+    Name,
+    Nickname,
+    Profile,
+    Description,
+    Message1,
+    Message2,
+    Message3,
+    Message4,
+    Note,
+}
+
+// Matches plugin parameters that are not displayed in-game: identifiers, file names, colors and JavaScript code
+lazy_static! {pub static ref PLUGIN_PARAMETER_IS_NOT_TEXT_RE: Regex = Regex::new(r"^(?:[a-z0-9_$]+|\w*_\w*|[a-z]+[A-Z]\w*|[A-Z][a-z0-9]+(?:[A-Z][a-z0-9]*)+|#[0-9a-fA-F]{3,8}|rgba?\(.*\)|\S+\.(?i:png|ogg|m4a|wav|mp3|js|json|txt|ttf|otf|woff2?|webm|mp4|efk))$|this\.|\$game|\bfunction\b|=>|^\s*(?:var|let|const|if|return)\s").unwrap();}
+
+// Separates location from text in the keys of translation maps, the same way gettext separates msgctxt from msgid
+pub const CONTEXT_SEPARATOR: char = '\u{4}';
+
+// Folders of the translation directory, that contain translation sections
+pub const SECTION_DIRS: [&str; 3] = ["maps", "other", "plugins"];
+
+// File of the translation directory, that stores the options of the last read
+pub const METADATA_FILE_NAME: &str = ".rvpacker-json-txt-metadata.json";
+
+lazy_static! {pub static ref STRING_IS_ONLY_SYMBOLS_RE: Regex = Regex::new(r#"^[.()+\-:;\[\]^~%&!№$@`*\/→×？?ｘ％▼|♥♪！：〜『』「」〽。…‥＝゠、，【】［］｛｝（）〔〕｟｠〘〙〈〉《》・\\#'"<>=_ー※▶ⅠⅰⅡⅱⅢⅲⅣⅳⅤⅴⅥⅵⅦⅶⅧⅷⅨⅸⅩⅹⅪⅺⅫⅻⅬⅼⅭⅽⅮⅾⅯⅿ\s0-9]+$"#).unwrap();}
+
+pub fn romanize_string<T>(string: T) -> String
+where
+    T: AsRef<str>,
+    std::string::String: std::convert::From<T>,
+{
+    let actual_string: String = String::from(string);
+    let mut result: String = String::new();
+
+    for char in actual_string.chars() {
+        let replacement: &str = match char {
+            '。' => ".",
+            '、' | '，' => ",",
+            '・' => "·",
+            '゠' => "–",
+            '＝' | 'ー' => "—",
+            '「' | '」' | '〈' | '〉' => "'",
+            '『' | '』' | '《' | '》' => "\"",
+            '（' | '〔' | '｟' | '〘' => "(",
+            '）' | '〕' | '｠' | '〙' => ")",
+            '｛' => "{",
+            '｝' => "}",
+            '［' | '【' | '〖' | '〚' => "[",
+            '］' | '】' | '〗' | '〛' => "]",
+            '〜' => "~",
+            '？' => "?",
+            '！' => "!",
+            '：' => ":",
+            '※' => "*",
+            '…' | '‥' => "...",
+            _ => {
+                result.push(char);
+                continue;
+            }
+        };
+
+        result.push_str(replacement);
+    }
+
+    result
+}
+
+/// Returns names of the translation sections of separate maps in the maps translation folder, keyed by the name of
+/// their map file without extension, e.g. `Map001_Forest` section is keyed by `Map001`.
+pub fn find_map_sections(path: &Path, format: Format) -> Result<HashMap<String, String>, Error> {
+    let mut sections: HashMap<String, String> = HashMap::new();
+
+    for entry in read_directory(path)?.flatten() {
+        let Ok(filename) = entry.file_name().into_string() else {
+            continue;
+        };
+
+        let name: Option<&str> = match format {
+            Format::Txt => filename.strip_suffix("_trans.txt"),
+            Format::Po => filename.strip_suffix(".po"),
+        };
+
+        if let Some(name) = name {
+            let stem: &str = name.split('_').next().unwrap_or_default();
+
+            if stem.len() > 3 && stem.starts_with("Map") && stem[3..].bytes().all(|byte: u8| byte.is_ascii_digit()) {
+                sections.insert(stem.to_string(), name.to_string());
+            }
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Returns names of the sections in the translation folder, which translation files exist in the specified format, in
+/// alphabetical order.
+pub fn section_names(path: &Path, format: Format) -> Result<Vec<String>, Error> {
+    let mut names: Vec<String> = read_directory(path)?
+        .flatten()
+        .filter_map(|entry: DirEntry| {
+            let filename: String = entry.file_name().into_string().ok()?;
+
+            match format {
+                Format::Txt => {
+                    let name: &str = filename.strip_suffix(".txt")?;
+
+                    if name.ends_with("_trans") || !path.join(format!("{name}_trans.txt")).exists() {
+                        return None;
+                    }
+
+                    Some(name.to_string())
+                }
+                Format::Po => filename.strip_suffix(".po").map(str::to_string),
+            }
+        })
+        .collect();
+
+    names.sort();
+    Ok(names)
+}
+
+/// Strips the `var $plugins =` prefix and the trailing semicolon from plugins.js content, leaving only the JSON array.
+pub fn strip_plugins_prefix(content: &str) -> &str {
+    match (content.find('['), content.rfind(']')) {
+        (Some(start), Some(end)) if start < end => &content[start..=end],
+        _ => content,
+    }
+}
+
+/// Decodes plugin parameter, that is itself a JSON-encoded struct, list or string, as plugins made with Yanfly's or
+/// VisuStella's plugin parameters structure store them. Returns None if parameter is plain text.
+pub fn decode_plugin_parameter(parameter: &str) -> Option<Value> {
+    if !parameter.starts_with(['[', '{', '"']) {
+        return None;
+    }
+
+    match from_str::<Value>(parameter) {
+        Ok(value) if value.is_array() || value.is_object() || value.is_str() => Some(value),
+        _ => None,
+    }
+}
+
+/// Returns the built-in profile, which fingerprint the game matches: `versionId` of System.json, names of the plugins
+/// of plugins.js and files of the game directory. Title of the game isn't used, since it's translated.
+pub fn detect_game_profile(system_file_path: &Path, plugins_file_path: &Path, game_path: &Path) -> Option<GameProfile> {
+    let system_obj: Value = read_json(system_file_path).ok()?;
+
+    let plugins: Vec<String> = read_to_string(plugins_file_path)
+        .ok()
+        .and_then(|content: String| from_str::<Vec<Value>>(strip_plugins_prefix(&content)).ok())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|plugin: &Value| plugin["name"].as_str().map(str::to_string))
+        .collect();
+
+    GameProfile::detect(&GameInfo {
+        version_id: system_obj["versionId"].as_u64(),
+        plugins,
+        game_path,
+    })
+}
+
+/// Translatable fields of the objects of a database file.
+pub struct DatabaseFields {
+    /// Fields of the objects and their kinds
+    pub fields: &'static [(&'static str, Variable)],
+    /// Arrays of the objects, which elements have translatable fields too, e.g. learnings of classes
+    pub nested: &'static [(&'static str, &'static [(&'static str, Variable)])],
+}
+
+// Fields of the database files, that are displayed in-game or in the debug menu, by the name of the file
+const DATABASE_FIELDS: [(&str, DatabaseFields); 13] = [
+    (
+        "Actors",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("nickname", Variable::Nickname),
+                ("profile", Variable::Profile),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+    (
+        "Animations",
+        DatabaseFields {
+            fields: &[("name", Variable::Name)],
+            nested: &[],
+        },
+    ),
+    (
+        "Armors",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("description", Variable::Description),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+    (
+        "Classes",
+        DatabaseFields {
+            fields: &[("name", Variable::Name), ("note", Variable::Note)],
+            nested: &[("learnings", &[("note", Variable::Note)])],
+        },
+    ),
+    (
+        "CommonEvents",
+        DatabaseFields {
+            fields: &[],
+            nested: &[],
+        },
+    ),
+    (
+        "Enemies",
+        DatabaseFields {
+            fields: &[("name", Variable::Name), ("note", Variable::Note)],
+            nested: &[],
+        },
+    ),
+    (
+        "Items",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("description", Variable::Description),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+    (
+        "MapInfos",
+        DatabaseFields {
+            fields: &[("name", Variable::Name)],
+            nested: &[],
+        },
+    ),
+    (
+        "Skills",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("description", Variable::Description),
+                ("message1", Variable::Message1),
+                ("message2", Variable::Message2),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+    (
+        "States",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("message1", Variable::Message1),
+                ("message2", Variable::Message2),
+                ("message3", Variable::Message3),
+                ("message4", Variable::Message4),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+    (
+        "Tilesets",
+        DatabaseFields {
+            fields: &[("name", Variable::Name), ("note", Variable::Note)],
+            nested: &[],
+        },
+    ),
+    (
+        "Troops",
+        DatabaseFields {
+            fields: &[("name", Variable::Name)],
+            nested: &[],
+        },
+    ),
+    (
+        "Weapons",
+        DatabaseFields {
+            fields: &[
+                ("name", Variable::Name),
+                ("description", Variable::Description),
+                ("note", Variable::Note),
+            ],
+            nested: &[],
+        },
+    ),
+];
+
+// Fields of the other files, e.g. of the databases of plugins, that are parsed, if they're present
+const DEFAULT_DATABASE_FIELDS: DatabaseFields = DatabaseFields {
+    fields: &[
+        ("name", Variable::Name),
+        ("nickname", Variable::Nickname),
+        ("description", Variable::Description),
+        ("message1", Variable::Message1),
+        ("message2", Variable::Message2),
+        ("message3", Variable::Message3),
+        ("message4", Variable::Message4),
+        ("note", Variable::Note),
+    ],
+    nested: &[],
+};
+
+/// Returns translatable fields of the objects of the database file, e.g. `Actors.json`.
+pub fn database_fields(filename: &str) -> &'static DatabaseFields {
+    let real_name: &str = filename.split_once('.').map_or(filename, |(real_name, _)| real_name);
+
+    DATABASE_FIELDS
+        .iter()
+        .find(|(name, _)| *name == real_name)
+        .map_or(&DEFAULT_DATABASE_FIELDS, |(_, fields)| fields)
+}
+
+/// Returns whether the file is a map file of the game, e.g. `Map001.json`.
+pub fn is_map_file(filename: &str) -> bool {
+    filename.starts_with("Map")
+        && filename.as_bytes().get(3).is_some_and(u8::is_ascii_digit)
+        && filename.ends_with(".json")
+}
+
+/// Returns whether the file is a database file of the game, that is parsed to the other translation folder, e.g.
+/// `Actors.json` or `CommonEvents.json`.
+pub fn is_other_file(filename: &str) -> bool {
+    let Some((real_name, extension)) = filename.split_once('.') else {
+        return false;
+    };
+
+    (!real_name.starts_with("Map") || real_name == "MapInfos") && real_name != "System" && extension == "json"
+}
+
+/// Reads and parses .json game files of the directory, which names match the filter. Files, that can't be read or
+/// parsed, are skipped, and their errors are pushed to errors, so the rest of the files can still be processed. Files
+/// with non-UTF-8 names are skipped, since game files never have such names.
+pub fn read_game_files<T: for<'de> Deserialize<'de> + Send>(
+    path: &Path,
+    filter: impl Fn(&str) -> bool,
+    errors: &mut Vec<Error>,
+) -> Vec<(String, T)> {
+    let entries: ReadDir = match read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => {
+            errors.push(Error::Io(path.to_path_buf(), error));
+            return Vec::new();
+        }
+    };
+
+    let mut files: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry: DirEntry| {
+            let filename: String = entry.file_name().into_string().ok()?;
+            filter(&filename).then(|| (filename, entry.path()))
+        })
+        .collect();
+
+    // Files are processed in the order of their names, so the output doesn't depend on the order of directory entries
+    files.sort_unstable();
+
+    let results: Vec<Result<(String, T), Error>> = files
+        .into_par_iter()
+        .map(|(filename, path)| read_json(&path).map(|value: T| (filename, value)))
+        .collect();
+
+    results
+        .into_iter()
+        .filter_map(|result: Result<(String, T), Error>| result.map_err(|error: Error| errors.push(error)).ok())
+        .collect()
+}
+
+/// Returns the directory, that contains `data`, `js`, `img` and `audio` folders of the game. Deployed RPG Maker MV
+/// games keep them in the `www` folder, while other games keep them in the root of the game directory.
+pub fn find_game_path(input_dir: &Path) -> PathBuf {
+    let www_path: PathBuf = input_dir.join("www");
+
+    if !input_dir.join("data").exists() && www_path.join("data").exists() {
+        www_path
+    } else {
+        input_dir.to_path_buf()
+    }
+}
+
+/// Returns the directory with original game files: `original` folder of the input directory, or `data` folder, if the
+/// former doesn't exist. Both folders are also looked up in the `www` folder of deployed games.
+pub fn find_original_path(input_dir: &Path) -> Option<PathBuf> {
+    [input_dir.to_path_buf(), find_game_path(input_dir)]
+        .into_iter()
+        .flat_map(|path: PathBuf| [path.join("original"), path.join("data")])
+        .find(|path: &PathBuf| path.exists())
+}
+
+/// Returns the path to the directory, where the folder of the game (`data`, `js` etc.) is written, so that the output
+/// directory mirrors the layout of the game and can be copied over it.
+pub fn game_output_path(input_dir: &Path, output_path: &Path) -> PathBuf {
+    match find_game_path(input_dir).strip_prefix(input_dir) {
+        Ok(relative_path) => output_path.join(relative_path),
+        Err(_) => output_path.to_path_buf(),
+    }
+}
+
+/// Returns the path to plugins.js file of the game: `js/plugins.js` of the game directory, or of the directory, that
+/// contains the folder with original game files.
+pub fn find_plugins_file_path(input_dir: &Path, original_path: &Path, plugins_path: &Path) -> PathBuf {
+    let game_paths: [Option<PathBuf>; 2] = [
+        Some(find_game_path(input_dir)),
+        original_path.parent().map(Path::to_path_buf),
+    ];
+
+    // Older versions of the tool required plugins.js to be manually converted to plugins.json in the translation folder
+    game_paths
+        .into_iter()
+        .flatten()
+        .map(|path: PathBuf| path.join("js/plugins.js"))
+        .find(|path: &PathBuf| path.exists())
+        .unwrap_or_else(|| plugins_path.join("plugins.json"))
+}
+
+/// Returns the content of the metadata file, which options of the read are used by the next reads and writes.
+pub fn metadata_content(
+    romanize: bool,
+    disable_custom_processing: bool,
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+    split_maps: bool,
+    speaker_context: bool,
+    codes: &[u64],
+    placeholders: bool,
+) -> String {
+    format!(
+        r#"{{"romanize":{romanize},"disableCustomProcessing":{disable_custom_processing},"format":"{}","contextualKeys":{contextual_keys},"locations":{locations},"splitMaps":{split_maps},"speakerContext":{speaker_context},"codes":[{}],"placeholders":{placeholders}}}"#,
+        if format == Format::Po { "po" } else { "txt" },
+        codes.iter().map(u64::to_string).collect::<Vec<String>>().join(",")
+    )
+}
+
+/// Returns codes of the event commands from the metadata, if they were written to it.
+pub fn metadata_codes(metadata: &Object) -> Option<Vec<u64>> {
+    metadata
+        .get(&"codes")
+        .and_then(|codes: &Value| codes.as_array())
+        .map(|codes| codes.iter().filter_map(|code: &Value| code.as_u64()).collect())
+}
@@ -1,12 +1,9 @@
-use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use clap::{parser::ValueSource, value_parser, Arg, ArgAction, ArgMatches, Command};
 use color_print::{cformat, cstr};
 use fastrand::seed;
-use lazy_static::lazy_static;
-use regex::Regex;
-use sonic_rs::{from_str, JsonValueTrait, Object};
 use std::{
     env::args,
-    fs::{create_dir_all, read_to_string, write},
+    fmt::Display,
     io::stdin,
     path::{Path, PathBuf},
     process::exit,
@@ -14,57 +11,20 @@ use std::{
 };
 use sys_locale::get_locale;
 
-mod read;
-mod write;
-
-#[derive(PartialEq)]
-enum GameType {
-    Termina,
-}
-
-#[derive(PartialEq)]
-enum ProcessingMode {
-    Force,
-    Append,
-    Default,
-}
-
-impl AsRef<ProcessingMode> for ProcessingMode {
-    fn as_ref(&self) -> &ProcessingMode {
-        self
-    }
-}
-
-impl PartialEq<ProcessingMode> for &ProcessingMode {
-    fn eq(&self, other: &ProcessingMode) -> bool {
-        *self == other
-    }
-}
-
-enum Code {
-    Dialogue, // also goes for credit
-    Choice,
-    System,
-    Unknown,
-}
+use rvpacker_json_txt::{
+    assets, check,
+    error::Error,
+    find_game_path,
+    profile::GameProfile,
+    project::{MetadataOption, Project, ProjectMessages, ProjectOptions, Section},
+    stats, xliff, Format, ProcessingMode,
+};
 
 enum Language {
     English,
     Russian,
 }
 
-#[derive(PartialEq)]
-enum Variable {
-    Name,
-    Nickname,
-    Description,
-    Message1,
-    Message2,
-    Message3,
-    Message4,
-    Note,
-}
-
 struct ProgramLocalization<'a> {
     // About message and templates
     about_msg: &'a str,
@@ -74,6 +34,13 @@ struct ProgramLocalization<'a> {
     // Command descriptions
     read_command_desc: &'a str,
     write_command_desc: &'a str,
+    export_xliff_command_desc: &'a str,
+    import_xliff_command_desc: &'a str,
+    verify_command_desc: &'a str,
+    decrypt_assets_command_desc: &'a str,
+    encrypt_assets_command_desc: &'a str,
+    stats_command_desc: &'a str,
+    check_command_desc: &'a str,
 
     // Argument descriptions
     input_dir_arg_read_desc: &'a str,
@@ -82,15 +49,39 @@ struct ProgramLocalization<'a> {
     output_dir_arg_read_desc: &'a str,
     output_dir_arg_write_desc: &'a str,
 
+    data_dir_arg_desc: &'a str,
+    plugins_file_arg_desc: &'a str,
+    translation_dir_arg_desc: &'a str,
+
     shuffle_level_arg_desc: &'a str,
+    wrap_width_arg_desc: &'a str,
+    wrap_lines_arg_desc: &'a str,
     disable_processing_arg_desc: &'a str,
 
     romanize_desc: &'a str,
+    format_arg_desc: &'a str,
+    enable_codes_arg_desc: &'a str,
+    disable_codes_arg_desc: &'a str,
+
+    xliff_file_arg_desc: &'a str,
+    json_arg_desc: &'a str,
+    ignore_untranslated_arg_desc: &'a str,
+    source_language_arg_desc: &'a str,
+    target_language_arg_desc: &'a str,
 
     force_arg_desc: &'a str,
     append_arg_desc: &'a str,
+    contextual_keys_arg_desc: &'a str,
+    locations_arg_desc: &'a str,
+    split_maps_arg_desc: &'a str,
+    global_dedup_arg_desc: &'a str,
+    speaker_context_arg_desc: &'a str,
+    placeholders_arg_desc: &'a str,
 
     disable_custom_processing_desc: &'a str,
+    profile_arg_desc: &'a str,
+    game_arg_desc: &'a str,
+    rules_arg_desc: &'a str,
 
     language_arg_desc: &'a str,
 
@@ -100,17 +91,43 @@ struct ProgramLocalization<'a> {
     // Argument types
     input_dir_arg_type: &'a str,
     output_dir_arg_type: &'a str,
+    data_dir_arg_type: &'a str,
+    plugins_file_arg_type: &'a str,
+    translation_dir_arg_type: &'a str,
+    profile_arg_type: &'a str,
+    game_arg_type: &'a str,
+    rules_arg_type: &'a str,
     disable_processing_arg_type: &'a str,
     shuffle_arg_type: &'a str,
     language_arg_type: &'a str,
+    format_arg_type: &'a str,
+    xliff_file_arg_type: &'a str,
+    codes_arg_type: &'a str,
+    number_arg_type: &'a str,
 
     // Messages and warnings
     input_dir_not_exist: &'a str,
     output_dir_not_exist: &'a str,
+    data_dir_not_exist: &'a str,
+    plugins_file_not_exist: &'a str,
     original_dir_missing: &'a str,
     translation_dirs_missing: &'a str,
     file_written_msg: &'a str,
     file_parsed_msg: &'a str,
+    file_exported_msg: &'a str,
+    file_verified_msg: &'a str,
+    file_decrypted_msg: &'a str,
+    file_encrypted_msg: &'a str,
+    differences_found_msg: &'a str,
+    no_differences_msg: &'a str,
+    failures_msg: &'a str,
+    lines_count_mismatch_msg: &'a str,
+    escape_codes_mismatch_msg: &'a str,
+    line_breaks_mismatch_msg: &'a str,
+    trailing_whitespace_msg: &'a str,
+    untranslated_msg: &'a str,
+    problems_found_msg: &'a str,
+    no_problems_msg: &'a str,
     file_already_parsed_msg: &'a str,
     file_is_not_parsed_msg: &'a str,
     done_in_msg: &'a str,
@@ -118,6 +135,18 @@ struct ProgramLocalization<'a> {
     custom_processing_enabled_msg: &'a str,
     enabling_romanize_metadata_msg: &'a str,
     disabling_custom_processing_metadata_msg: &'a str,
+    enabling_po_format_metadata_msg: &'a str,
+    enabling_contextual_keys_metadata_msg: &'a str,
+    enabling_locations_metadata_msg: &'a str,
+    enabling_split_maps_metadata_msg: &'a str,
+    enabling_speaker_context_metadata_msg: &'a str,
+    enabling_placeholders_metadata_msg: &'a str,
+    placeholders_mismatch_msg: &'a str,
+    using_codes_metadata_msg: &'a str,
+
+    // Stats table
+    stats_columns: [&'a str; 8],
+    stats_total: &'a str,
 
     // Misc
     possible_values: &'a str,
@@ -145,6 +174,13 @@ impl<'a> ProgramLocalization<'a> {
             // Command descriptions
             read_command_desc: cstr!(r#"<bold>Parses files from "original" or "data" folders of input directory to "translation" folder of output directory.</bold>"#),
             write_command_desc: cstr!(r#"<bold>Writes translated files using original files from "original" or "data" folders of input directory and writes results to "output" folder of output directory.</bold>"#),
+            export_xliff_command_desc: cstr!(r#"<bold>Exports translation files from "translation" folder of input directory to a single XLIFF 2.0 file, that can be opened in CAT tools.</bold>"#),
+            import_xliff_command_desc: cstr!(r#"<bold>Imports translation from XLIFF 2.0 file back to translation files of "translation" folder of input directory.</bold>"#),
+            verify_command_desc: cstr!(r#"<bold>Verifies that reading and writing of the game is lossless: reads files from "original" or "data" folders of input directory to a temporary directory, writes them back using the original text as translation and reports every field of written files, that differs from the original files.</bold>"#),
+            decrypt_assets_command_desc: cstr!(r#"<bold>Decrypts encrypted images and audio (.rpgmvp, .rpgmvo, .rpgmvm, .png_, .ogg_, .m4a_) from "img" and "audio" folders of input directory to "translation/assets" folder, using the encryption key from System.json.</bold>"#),
+            encrypt_assets_command_desc: cstr!(r#"<bold>Encrypts images and audio from "translation/assets" folder to "output" folder, using the encryption key from System.json.</bold>"#),
+            stats_command_desc: cstr!(r#"<bold>Prints translation progress of every translation file of "translation" folder of input directory: numbers of translated, identical to the original and empty lines, and numbers of characters and words of the original text.</bold>"#),
+            check_command_desc: cstr!(r#"<bold>Checks translation files of "translation" folder of input directory for problems before writing: mismatched numbers of lines, lost or added escape codes and line breaks, trailing whitespace and untranslated lines.</bold>"#),
 
             // Argument descriptions
             input_dir_arg_read_desc: r#"Input directory, containing folder "original" or "data" with original game files."#,
@@ -153,15 +189,40 @@ impl<'a> ProgramLocalization<'a> {
             output_dir_arg_read_desc: r#"Output directory, where a "translation" folder with translation .txt files will be created."#,
             output_dir_arg_write_desc: r#"Output directory, where an "output" folder with "data" and "js" subfolders with game files with translated text from .txt files will be created."#,
 
+            data_dir_arg_desc: r#"Directory with original game files. By default, "original" or "data" folder of input directory, or of its "www" folder, is used."#,
+            plugins_file_arg_desc: r#"Path to plugins.js file. By default, "js/plugins.js" file of input directory, of its "www" folder, or next to the directory with original game files, is used."#,
+            translation_dir_arg_desc: r#"Directory with translation files. By default, "translation" folder of output directory is used."#,
+
             shuffle_level_arg_desc: "With value 1, shuffles all translation lines. With value 2, shuffles all words in translation lines.",
+            wrap_width_arg_desc: "Rewraps translation of messages to lines of the specified width in characters, adding show text commands, when translation doesn't fit into the original ones. Full-width symbols take two characters, and escape codes aren't counted, except for icons.",
+            wrap_lines_arg_desc: "Max number of lines in a message box, used with --wrap-width. Messages, that don't fit into the box, are split into several messages with the same face and speaker.",
             disable_processing_arg_desc: "Skips processing specified files.",
 
             romanize_desc: r#"If you parsing text from a Japanese game, that contains symbols like 「」, which are just the Japanese quotation marks, it automatically replaces these symbols by their roman equivalents (in this case, ""). This flag will automatically be used when writing if you parsed game text with it."#,
 
+            format_arg_desc: r#"Format of translation files. With "txt", every file is stored as a pair of original and _trans .txt files. With "po", every file is stored as a single gettext .po file, that can be edited in tools like Poedit or Weblate. This flag will automatically be used when writing if you parsed game text with it."#,
+            enable_codes_arg_desc: "Enables extraction of text from the event commands with specified codes, which are disabled by default: comments (108, 408) and scripts (355, 655). This option will automatically be used when writing if you parsed game text with it.",
+            disable_codes_arg_desc: "Disables extraction of text from the event commands with specified codes. This option will automatically be used when writing if you parsed game text with it.",
+
+            xliff_file_arg_desc: r#"Path to the XLIFF file. By default, "translation.xlf" file in the "translation" folder is used."#,
+            json_arg_desc: "Prints the report as JSON instead of a table.",
+            ignore_untranslated_arg_desc: "Doesn't report untranslated lines.",
+            source_language_arg_desc: "Language of the original game text, as a BCP 47 language tag.",
+            target_language_arg_desc: "Language of the translation, as a BCP 47 language tag.",
+
             force_arg_desc: "Force rewrite all files. Cannot be used with --append.",
             append_arg_desc: "When the game, which files you've parsed, or the rvpacker-json-txt updates, you probably should re-read game files using --append flag, to append any unparsed text to the existing without overwriting translation. Cannot be used with --force.",
+            contextual_keys_arg_desc: "For every line, that occurs in several locations, adds an entry for each of the locations, keyed by the location and the line. Translation of such entry is used only in its location instead of the translation of the line, and if it's left empty, the translation of the line is used.",
+            locations_arg_desc: "Additionally writes a .locations.json file for every translation file, that lists all locations of every line: file, event id, page and command index. These files are not used when writing.",
+            split_maps_arg_desc: "Writes a separate translation file for every map, named after the map file and its display name, instead of a single maps file. Both layouts can be used when writing.",
+            global_dedup_arg_desc: "When appending, pre-fills translation of lines in separate map files with their translation from other maps, including the single maps file. Only has effect when maps are split.",
+            speaker_context_arg_desc: r#"Adds speaker names of "Show Text" commands as comments to the dialogue lines they introduce. Comments are only written to .po files."#,
+            placeholders_arg_desc: r#"Replaces escape codes and plugin tags of event commands text with placeholders, e.g. \C[2] with {C2}, \. with {WAIT} and <WordWrap> with {WordWrap}, so they can't be broken accidentally. Placeholders are replaced back when writing, and translation, which placeholders don't match the original, isn't written. String literals of scripts and arguments of plugin commands aren't protected."#,
 
             disable_custom_processing_desc: "Disables built-in custom processing, implemented for some games. This flag will automatically be used when writing if you parsed game text with it.",
+            profile_arg_desc: "Path to a JSON game profile, that describes custom processing of the game: which strings are skipped, which plugins are parsed and how notes are handled. It's used instead of the built-in profile of the game. Pass the same profile when writing.",
+            game_arg_desc: "Uses the built-in profile of the game with the specified id for custom processing, instead of detecting the game by its fingerprint: versionId of System.json, plugins and files of the game.",
+            rules_arg_desc: "Path to a JSON file with rules, that include or exclude strings by regexes, scoped by sections, kinds of event commands and variables. By default, \"rules.json\" file of translation directory is used, if it exists. The rules are applied both when reading and when writing.",
             language_arg_desc: "Sets the localization of the tool to the selected language.",
 
             log_arg_desc: "Enables logging.",
@@ -170,17 +231,43 @@ impl<'a> ProgramLocalization<'a> {
             // Argument types
             input_dir_arg_type: "INPUT_PATH",
             output_dir_arg_type: "OUTPUT_PATH",
+            data_dir_arg_type: "DATA_PATH",
+            plugins_file_arg_type: "PLUGINS_PATH",
+            translation_dir_arg_type: "TRANSLATION_PATH",
+            profile_arg_type: "PROFILE_PATH",
+            game_arg_type: "GAME",
+            rules_arg_type: "RULES_PATH",
             disable_processing_arg_type: "FILENAMES",
             shuffle_arg_type: "NUMBER",
             language_arg_type: "LANGUAGE",
+            format_arg_type: "FORMAT",
+            xliff_file_arg_type: "XLIFF_PATH",
+            codes_arg_type: "CODES",
+            number_arg_type: "NUMBER",
 
             // Messages and warnings
             input_dir_not_exist: "Input directory does not exist.",
             output_dir_not_exist: "Output directory does not exist.",
+            data_dir_not_exist: "Directory with original game files does not exist.",
+            plugins_file_not_exist: "Plugins file does not exist.",
             original_dir_missing: r#"The "original" or "data" folder in the input directory does not exist."#,
             translation_dirs_missing: r#"The "translation/maps" and/or "translation/other" folders in the input directory do not exist."#,
             file_written_msg: "Wrote file",
             file_parsed_msg: "Parsed file",
+            file_exported_msg: "Exported file",
+            file_verified_msg: "Verified file",
+            file_decrypted_msg: "Decrypted file",
+            file_encrypted_msg: "Encrypted file",
+            differences_found_msg: "fields of written files differ from the original files.",
+            no_differences_msg: "Written files don't differ from the original files.",
+            failures_msg: "files couldn't be processed:",
+            lines_count_mismatch_msg: "number of lines differs from the original file, so translation of all the following lines is shifted",
+            escape_codes_mismatch_msg: "escape codes of translation differ from the original",
+            line_breaks_mismatch_msg: "number of line breaks of translation differs from the original",
+            trailing_whitespace_msg: "translation has trailing whitespace",
+            untranslated_msg: "line is not translated",
+            problems_found_msg: "Problems found:",
+            no_problems_msg: "No problems found.",
             file_already_parsed_msg: "file already exists. If you want to forcefully re-read all files, use --force flag, or --append if you want append new text to already existing files.",
             file_is_not_parsed_msg: "Files aren't already parsed. Continuing as if --append flag was omitted.",
             done_in_msg: "Done in:",
@@ -188,6 +275,18 @@ impl<'a> ProgramLocalization<'a> {
             custom_processing_enabled_msg: "Custom processing for this game will be used. Use --disable-custom-processing to disable it.",
             enabling_romanize_metadata_msg: "Enabling romanize according to the metadata from previous read.",
             disabling_custom_processing_metadata_msg: "Disabling custom processing according to the metadata from previous read.",
+            enabling_po_format_metadata_msg: "Using .po format according to the metadata from previous read.",
+            enabling_contextual_keys_metadata_msg: "Enabling contextual keys according to the metadata from previous read.",
+            enabling_locations_metadata_msg: "Enabling locations files according to the metadata from previous read.",
+            enabling_split_maps_metadata_msg: "Enabling separate map files according to the metadata from previous read.",
+            enabling_speaker_context_metadata_msg: "Enabling speaker comments according to the metadata from previous read.",
+            enabling_placeholders_metadata_msg: "Enabling placeholders according to the metadata from previous read.",
+            placeholders_mismatch_msg: "Translation isn't written, since its placeholders don't match the original:",
+            using_codes_metadata_msg: "Using event command codes according to the metadata from previous read.",
+
+            // Stats table
+            stats_columns: ["File", "Lines", "Translated", "Identical", "Empty", "Characters", "Words", "Progress"],
+            stats_total: "Total",
 
             // Misc
             possible_values: "Allowed values:",
@@ -206,6 +305,13 @@ impl<'a> ProgramLocalization<'a> {
 
             read_command_desc: cstr!(r#"<bold>Парсит файлы из папки "original" или "data" входной директории в папку "translation" выходной директории.</bold>"#),
             write_command_desc: cstr!(r#"<bold>Записывает переведенные файлы, используя исходные файлы из папки "original" или "data" входной директории, применяя текст из .txt файлов папки "translation", выводя результаты в папку "output" выходной директории.</bold>"#),
+            export_xliff_command_desc: cstr!(r#"<bold>Экспортирует файлы перевода из папки "translation" входной директории в один файл XLIFF 2.0, который можно открыть в CAT-инструментах.</bold>"#),
+            import_xliff_command_desc: cstr!(r#"<bold>Импортирует перевод из файла XLIFF 2.0 обратно в файлы перевода папки "translation" входной директории.</bold>"#),
+            verify_command_desc: cstr!(r#"<bold>Проверяет, что чтение и запись игры происходят без потерь: парсит файлы из папки "original" или "data" входной директории во временную директорию, записывает их обратно, используя оригинальный текст в качестве перевода, и выводит все поля записанных файлов, которые отличаются от оригинальных файлов.</bold>"#),
+            decrypt_assets_command_desc: cstr!(r#"<bold>Расшифровывает зашифрованные изображения и аудио (.rpgmvp, .rpgmvo, .rpgmvm, .png_, .ogg_, .m4a_) из папок "img" и "audio" входной директории в папку "translation/assets", используя ключ шифрования из System.json.</bold>"#),
+            encrypt_assets_command_desc: cstr!(r#"<bold>Шифрует изображения и аудио из папки "translation/assets" в папку "output", используя ключ шифрования из System.json.</bold>"#),
+            stats_command_desc: cstr!(r#"<bold>Выводит прогресс перевода каждого файла перевода папки "translation" входной директории: количество переведённых, идентичных оригиналу и пустых строк, а также количество символов и слов оригинального текста.</bold>"#),
+            check_command_desc: cstr!(r#"<bold>Проверяет файлы перевода папки "translation" входной директории на наличие проблем перед записью: несовпадающее количество строк, потерянные или добавленные escape-коды и переносы строк, пробелы в конце строк и непереведённые строки.</bold>"#),
 
             input_dir_arg_read_desc: r#"Входная директория, содержащая папку "original" или "data" с оригинальными файлами игры."#,
             input_dir_arg_write_desc: r#"Входная директория, содержащая папку "original" или "data" с оригинальными файлами игры, а также папку "translation" с .txt файлами перевода."#,
@@ -213,15 +319,40 @@ impl<'a> ProgramLocalization<'a> {
             output_dir_arg_read_desc: r#"Выходная директория, где будет создана папка "translation" с .txt файлами перевода."#,
             output_dir_arg_write_desc: r#"Выходная директория, где будет создана папка "output" с подпапками "data" и "js", содержащими игровые файлы с переведённым текстом из .txt файлов."#,
 
+            data_dir_arg_desc: r#"Директория с оригинальными файлами игры. По умолчанию используется папка "original" или "data" входной директории или её папки "www"."#,
+            plugins_file_arg_desc: r#"Путь к файлу plugins.js. По умолчанию используется файл "js/plugins.js" входной директории, её папки "www" или рядом с директорией с оригинальными файлами игры."#,
+            translation_dir_arg_desc: r#"Директория с файлами перевода. По умолчанию используется папка "translation" выходной директории."#,
+
             shuffle_level_arg_desc: "При значении 1, перемешивает все строки перевода. При значении 2, перемешивает все слова в строках перевода.",
+            wrap_width_arg_desc: "Переносит перевод сообщений по строкам указанной ширины в символах, добавляя команды показа текста, если перевод не помещается в исходные. Полноширинные символы занимают два символа, а escape-коды не учитываются, кроме иконок.",
+            wrap_lines_arg_desc: "Максимальное количество строк в окне сообщения, используется вместе с --wrap-width. Сообщения, которые не помещаются в окно, разбиваются на несколько сообщений с тем же лицом и говорящим.",
             disable_processing_arg_desc: "Не обрабатывает указанные файлы.",
 
             romanize_desc: r#"Если вы парсите текст из японскной игры, содержащей символы вроде 「」, являющимися обычными японскими кавычками, программа автоматически заменяет эти символы на их европейские эквиваленты. (в данном случае, "")"#,
 
+            format_arg_desc: r#"Формат файлов перевода. При значении "txt", каждый файл хранится как пара из оригинального и _trans .txt файлов. При значении "po", каждый файл хранится как один .po файл gettext, который можно редактировать в инструментах вроде Poedit или Weblate. Этот флаг будет автоматически применён при записи, если текст игры был прочитан с его использованием."#,
+            enable_codes_arg_desc: "Включает извлечение текста из команд событий с указанными кодами, которые выключены по умолчанию: комментарии (108, 408) и скрипты (355, 655). Этот аргумент будет автоматически применён при записи, если текст игры был прочитан с его использованием.",
+            disable_codes_arg_desc: "Выключает извлечение текста из команд событий с указанными кодами. Этот аргумент будет автоматически применён при записи, если текст игры был прочитан с его использованием.",
+
+            xliff_file_arg_desc: r#"Путь к файлу XLIFF. По умолчанию используется файл "translation.xlf" в папке "translation"."#,
+            json_arg_desc: "Выводит отчёт в формате JSON вместо таблицы.",
+            ignore_untranslated_arg_desc: "Не сообщает о непереведённых строках.",
+            source_language_arg_desc: "Язык оригинального текста игры в виде языкового тега BCP 47.",
+            target_language_arg_desc: "Язык перевода в виде языкового тега BCP 47.",
+
             force_arg_desc: "Принудительно перезаписать все файлы. Не может быть использован с --append.",
             append_arg_desc: "Когда игра, файлы которой вы распарсили, либо же rvpacker-json-txt обновляется, вы, наверное, должны перечитать файлы игры используя флаг --append, чтобы добавить любой нераспарсенный текст к имеющемуся без перезаписи прогресса. Не может быть использован с --force.",
+            contextual_keys_arg_desc: "Для каждой строки, встречающейся в нескольких местах, добавляет запись для каждого из этих мест, ключом которой являются место и строка. Перевод такой записи используется только в её месте вместо перевода строки, а если он оставлен пустым, используется перевод строки.",
+            locations_arg_desc: "Дополнительно записывает .locations.json файл для каждого файла перевода, перечисляющий все места каждой строки: файл, id события, страницу и индекс команды. Эти файлы не используются при записи.",
+            split_maps_arg_desc: "Записывает отдельный файл перевода для каждой карты, названный по файлу карты и её отображаемому имени, вместо одного файла карт. Оба варианта могут быть использованы при записи.",
+            global_dedup_arg_desc: "При добавлении текста, заполняет перевод строк в отдельных файлах карт их переводом из других карт, включая единый файл карт. Действует только при отдельных файлах карт.",
+            speaker_context_arg_desc: r#"Добавляет имена говорящих из команд "Show Text" в качестве комментариев к строкам диалога, которые они начинают. Комментарии записываются только в .po файлы."#,
+            placeholders_arg_desc: r#"Заменяет escape-коды и теги плагинов в тексте команд событий на плейсхолдеры, например \C[2] на {C2}, \. на {WAIT} и <WordWrap> на {WordWrap}, чтобы их нельзя было случайно сломать. При записи плейсхолдеры заменяются обратно, а перевод, плейсхолдеры которого не совпадают с оригиналом, не записывается. Строковые литералы скриптов и аргументы команд плагинов не защищаются."#,
 
             disable_custom_processing_desc: "Отключает использование индивидуальных способов обработки текста, имплементированных для некоторых игр. Этот флаг будет автоматически применён при записи, если текст игры был прочитан с его использованием.",
+            profile_arg_desc: "Путь к JSON-профилю игры, который описывает индивидуальную обработку текста игры: какие строки пропускаются, какие плагины парсятся и как обрабатываются заметки. Используется вместо встроенного профиля игры. Передавайте тот же профиль при записи.",
+            game_arg_desc: "Использует встроенный профиль игры с указанным идентификатором для индивидуальной обработки текста вместо определения игры по её отпечатку: versionId из System.json, плагинам и файлам игры.",
+            rules_arg_desc: "Путь к JSON-файлу с правилами, которые включают или исключают строки по регулярным выражениям с учётом разделов, видов команд событий и переменных. По умолчанию используется файл \"rules.json\" директории перевода, если он существует. Правила применяются и при чтении, и при записи.",
             language_arg_desc: "Устанавливает локализацию инструмента на выбранный язык.",
 
             log_arg_desc: "Включает логирование.",
@@ -229,16 +360,42 @@ impl<'a> ProgramLocalization<'a> {
 
             input_dir_arg_type: "ВХОДНОЙ_ПУТЬ",
             output_dir_arg_type: "ВЫХОДНОЙ_ПУТЬ",
+            data_dir_arg_type: "ПУТЬ_ДАННЫХ",
+            plugins_file_arg_type: "ПУТЬ_ПЛАГИНОВ",
+            translation_dir_arg_type: "ПУТЬ_ПЕРЕВОДА",
+            profile_arg_type: "ПУТЬ_ПРОФИЛЯ",
+            game_arg_type: "ИГРА",
+            rules_arg_type: "ПУТЬ_ПРАВИЛ",
             disable_processing_arg_type: "ИМЕНА_ФАЙЛОВ",
             shuffle_arg_type: "ЦИФРА",
             language_arg_type: "ЯЗЫК",
+            format_arg_type: "ФОРМАТ",
+            xliff_file_arg_type: "ПУТЬ_XLIFF",
+            codes_arg_type: "КОДЫ",
+            number_arg_type: "ЧИСЛО",
 
             input_dir_not_exist: "Входная директория не существует.",
             output_dir_not_exist: "Выходная директория не существует.",
+            data_dir_not_exist: "Директория с оригинальными файлами игры не существует.",
+            plugins_file_not_exist: "Файл плагинов не существует.",
             original_dir_missing: r#"Папка "original" или "data" входной директории не существует."#,
             translation_dirs_missing: r#"Папки "translation/maps" и/или "translation/other" входной директории не существуют."#,
             file_written_msg: "Записан файл",
             file_parsed_msg: "Распарсен файл",
+            file_exported_msg: "Экспортирован файл",
+            file_verified_msg: "Проверен файл",
+            file_decrypted_msg: "Расшифрован файл",
+            file_encrypted_msg: "Зашифрован файл",
+            differences_found_msg: "полей записанных файлов отличаются от оригинальных файлов.",
+            no_differences_msg: "Записанные файлы не отличаются от оригинальных файлов.",
+            failures_msg: "файлов не удалось обработать:",
+            lines_count_mismatch_msg: "количество строк отличается от оригинального файла, поэтому перевод всех следующих строк смещён",
+            escape_codes_mismatch_msg: "escape-коды перевода отличаются от оригинала",
+            line_breaks_mismatch_msg: "количество переносов строк перевода отличается от оригинала",
+            trailing_whitespace_msg: "перевод содержит пробелы в конце строки",
+            untranslated_msg: "строка не переведена",
+            problems_found_msg: "Найдено проблем:",
+            no_problems_msg: "Проблем не найдено.",
             file_already_parsed_msg: "уже существует. Если вы хотите принудительно перезаписать все файлы, используйте флаг --force, или --append если вы хотите добавить новый текст в файлы.",
             file_is_not_parsed_msg: "Файлы ещё не распарсены. Продолжаем в режиме с выключенным флагом --append.",
             done_in_msg: "Выполнено за:",
@@ -246,6 +403,17 @@ impl<'a> ProgramLocalization<'a> {
             custom_processing_enabled_msg: "Индивидуальная обработка текста будет использована для этой игры. Используйте --disable-custom-processing, чтобы отключить её.",
             enabling_romanize_metadata_msg: "В соответствии с метаданными из прошлого чтения, романизация текста будет использована.",
             disabling_custom_processing_metadata_msg: "В соответсвии с метаданными из прошлого чтения, индивидуальная обработка текста будет выключена.",
+            enabling_po_format_metadata_msg: "В соответствии с метаданными из прошлого чтения, будет использован формат .po.",
+            enabling_contextual_keys_metadata_msg: "В соответствии с метаданными из прошлого чтения, контекстные ключи будут использованы.",
+            enabling_locations_metadata_msg: "В соответствии с метаданными из прошлого чтения, файлы мест строк будут записаны.",
+            enabling_split_maps_metadata_msg: "В соответствии с метаданными из прошлого чтения, будут записаны отдельные файлы карт.",
+            enabling_speaker_context_metadata_msg: "В соответствии с метаданными из прошлого чтения, имена говорящих будут добавлены в комментарии.",
+            enabling_placeholders_metadata_msg: "В соответствии с метаданными из прошлого чтения, плейсхолдеры будут использованы.",
+            placeholders_mismatch_msg: "Перевод не записан, так как его плейсхолдеры не совпадают с оригиналом:",
+            using_codes_metadata_msg: "В соответствии с метаданными из прошлого чтения, будут использованы коды команд событий из них.",
+
+            stats_columns: ["Файл", "Строки", "Переведено", "Идентично", "Пусто", "Символы", "Слова", "Прогресс"],
+            stats_total: "Всего",
 
             possible_values: "Разрешённые значения:",
             example: "Пример:",
@@ -256,60 +424,6 @@ impl<'a> ProgramLocalization<'a> {
     }
 }
 
-lazy_static! {pub static ref STRING_IS_ONLY_SYMBOLS_RE: Regex = Regex::new(r#"^[.()+\-:;\[\]^~%&!№$@`*\/→×？?ｘ％▼|♥♪！：〜『』「」〽。…‥＝゠、，【】［］｛｝（）〔〕｟｠〘〙〈〉《》・\\#'"<>=_ー※▶ⅠⅰⅡⅱⅢⅲⅣⅳⅤⅴⅥⅵⅦⅶⅧⅷⅨⅸⅩⅹⅪⅺⅫⅻⅬⅼⅭⅽⅮⅾⅯⅿ\s0-9]+$"#).unwrap();}
-
-pub fn romanize_string<T>(string: T) -> String
-where
-    T: AsRef<str>,
-    std::string::String: std::convert::From<T>,
-{
-    let actual_string: String = String::from(string);
-    let mut result: String = String::new();
-
-    for char in actual_string.chars() {
-        let replacement: &str = match char {
-            '。' => ".",
-            '、' | '，' => ",",
-            '・' => "·",
-            '゠' => "–",
-            '＝' | 'ー' => "—",
-            '「' | '」' | '〈' | '〉' => "'",
-            '『' | '』' | '《' | '》' => "\"",
-            '（' | '〔' | '｟' | '〘' => "(",
-            '）' | '〕' | '｠' | '〙' => ")",
-            '｛' => "{",
-            '｝' => "}",
-            '［' | '【' | '〖' | '〚' => "[",
-            '］' | '】' | '〗' | '〛' => "]",
-            '〜' => "~",
-            '？' => "?",
-            '！' => "!",
-            '：' => ":",
-            '※' => "*",
-            '…' | '‥' => "...",
-            _ => {
-                result.push(char);
-                continue;
-            }
-        };
-
-        result.push_str(replacement);
-    }
-
-    result
-}
-
-fn get_game_type(system_file_path: &Path) -> Option<GameType> {
-    let system_obj: Object = from_str(&read_to_string(system_file_path).unwrap()).unwrap();
-    let game_title: String = system_obj["gameTitle"].as_str().unwrap().to_lowercase();
-
-    if game_title.contains("termina") {
-        return Some(GameType::Termina);
-    }
-
-    None
-}
-
 // this function probably should be replaced by some clap-native equivalent
 fn preparse_arguments() -> (Language, Option<String>) {
     let mut locale: String = get_locale().unwrap_or_else(|| String::from("en_US"));
@@ -338,11 +452,72 @@ fn preparse_arguments() -> (Language, Option<String>) {
     }
 }
 
+/// Prints the summary of the files, that failed to be processed, if there's any.
+fn print_errors(errors: &[Error], failures_msg: &str) {
+    if errors.is_empty() {
+        return;
+    }
+
+    eprintln!("{} {failures_msg}", errors.len());
+
+    for error in errors {
+        eprintln!("  {error}");
+    }
+}
+
+/// Prints the message of the error, that prevents the program from running, and exits.
+fn exit_with_error(message: impl Display) -> ! {
+    eprintln!("{message}");
+    exit(1);
+}
+
+/// Returns the localized message of the error, that prevents creating the project.
+fn project_error_msg(
+    error: &Error,
+    input_dir: &Path,
+    data_dir_specified: bool,
+    localization: &ProgramLocalization,
+) -> String {
+    match error {
+        Error::MissingDirectory(path) if path == input_dir => localization.input_dir_not_exist.to_string(),
+        Error::MissingDirectory(_) if data_dir_specified => localization.data_dir_not_exist.to_string(),
+        Error::MissingDirectory(path) if path.starts_with(input_dir) => localization.original_dir_missing.to_string(),
+        Error::MissingDirectory(_) => localization.output_dir_not_exist.to_string(),
+        Error::MissingFile(_) => localization.plugins_file_not_exist.to_string(),
+        _ => error.to_string(),
+    }
+}
+
+/// Prints the localized messages of the options, that were taken from the metadata of the previous read.
+fn print_metadata_options(changed_options: &Result<Vec<MetadataOption>, Error>, localization: &ProgramLocalization) {
+    let changed_options: &Vec<MetadataOption> = match changed_options {
+        Ok(changed_options) => changed_options,
+        Err(error) => exit_with_error(error),
+    };
+
+    for option in changed_options {
+        println!(
+            "{}",
+            match option {
+                MetadataOption::Romanize => localization.enabling_romanize_metadata_msg,
+                MetadataOption::DisableCustomProcessing => localization.disabling_custom_processing_metadata_msg,
+                MetadataOption::PoFormat => localization.enabling_po_format_metadata_msg,
+                MetadataOption::ContextualKeys => localization.enabling_contextual_keys_metadata_msg,
+                MetadataOption::Locations => localization.enabling_locations_metadata_msg,
+                MetadataOption::SplitMaps => localization.enabling_split_maps_metadata_msg,
+                MetadataOption::SpeakerContext => localization.enabling_speaker_context_metadata_msg,
+                MetadataOption::Placeholders => localization.enabling_placeholders_metadata_msg,
+                MetadataOption::Codes => localization.using_codes_metadata_msg,
+            }
+        );
+    }
+}
+
 fn main() {
     let start_time: Instant = Instant::now();
 
     let (language, subcommand): (Language, Option<String>) = preparse_arguments();
-    let localization: ProgramLocalization = ProgramLocalization::new(language);
+    let localization: ProgramLocalization<'static> = ProgramLocalization::new(language);
 
     let (input_dir_arg_desc, output_dir_arg_desc) = if let Some(subcommand) = subcommand {
         match subcommand.as_str() {
@@ -397,6 +572,30 @@ fn main() {
         .hide_default_value(true)
         .display_order(1);
 
+    let data_dir_arg: Arg = Arg::new("data-dir")
+        .long("data-dir")
+        .global(true)
+        .help(localization.data_dir_arg_desc)
+        .value_name(localization.data_dir_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(1);
+
+    let plugins_file_arg: Arg = Arg::new("plugins-file")
+        .long("plugins-file")
+        .global(true)
+        .help(localization.plugins_file_arg_desc)
+        .value_name(localization.plugins_file_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(1);
+
+    let translation_dir_arg: Arg = Arg::new("translation-dir")
+        .long("translation-dir")
+        .global(true)
+        .help(localization.translation_dir_arg_desc)
+        .value_name(localization.translation_dir_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(1);
+
     let shuffle_level_arg: Arg = Arg::new("shuffle-level")
         .short('s')
         .long("shuffle-level")
@@ -414,6 +613,33 @@ fn main() {
         .hide_default_value(true)
         .display_order(2);
 
+    let wrap_width_arg: Arg = Arg::new("wrap-width")
+        .long("wrap-width")
+        .action(ArgAction::Set)
+        .value_name(localization.number_arg_type)
+        .value_parser(value_parser!(u16).range(1..))
+        .help(cformat!(
+            "{}\n{} --wrap-width 50.",
+            localization.wrap_width_arg_desc,
+            localization.example,
+        ))
+        .display_order(3);
+
+    let wrap_lines_arg: Arg = Arg::new("wrap-lines")
+        .long("wrap-lines")
+        .action(ArgAction::Set)
+        .value_name(localization.number_arg_type)
+        .default_value("4")
+        .value_parser(value_parser!(u16).range(1..))
+        .help(cformat!(
+            "{}\n{} --wrap-lines 3.<bold>\n[{} 4]</bold>",
+            localization.wrap_lines_arg_desc,
+            localization.example,
+            localization.default_value,
+        ))
+        .hide_default_value(true)
+        .display_order(4);
+
     let disable_processing_arg: Arg = Arg::new("disable-processing")
         .long("disable-processing")
         .value_delimiter(',')
@@ -436,6 +662,77 @@ fn main() {
         .help(localization.romanize_desc)
         .display_order(4);
 
+    let format_arg: Arg = Arg::new("format")
+        .long("format")
+        .global(true)
+        .value_name(localization.format_arg_type)
+        .help(cformat!(
+            "{}\n{} --format po.<bold>\n[{} txt, po]\n[{} txt]</bold>",
+            localization.format_arg_desc,
+            localization.example,
+            localization.possible_values,
+            localization.default_value,
+        ))
+        .value_parser(["txt", "po"])
+        .default_value("txt")
+        .hide_default_value(true)
+        .display_order(5);
+
+    let enable_codes_arg: Arg = Arg::new("enable-codes")
+        .long("enable-codes")
+        .global(true)
+        .value_delimiter(',')
+        .value_name(localization.codes_arg_type)
+        .help(cformat!(
+            "{}\n{} --enable-codes=108,408.",
+            localization.enable_codes_arg_desc,
+            localization.example,
+        ))
+        .value_parser(value_parser!(u64))
+        .display_order(6);
+
+    let disable_codes_arg: Arg = Arg::new("disable-codes")
+        .long("disable-codes")
+        .global(true)
+        .value_delimiter(',')
+        .value_name(localization.codes_arg_type)
+        .help(cformat!(
+            "{}\n{} --disable-codes=320,324,325.",
+            localization.disable_codes_arg_desc,
+            localization.example,
+        ))
+        .value_parser(value_parser!(u64))
+        .display_order(7);
+
+    let xliff_file_arg: Arg = Arg::new("xliff-file")
+        .long("xliff-file")
+        .help(localization.xliff_file_arg_desc)
+        .value_name(localization.xliff_file_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(6);
+
+    let source_language_arg: Arg = Arg::new("source-language")
+        .long("source-language")
+        .help(cformat!(
+            "{}\n{} --source-language ja.",
+            localization.source_language_arg_desc,
+            localization.example,
+        ))
+        .value_name(localization.language_arg_type)
+        .required(true)
+        .display_order(7);
+
+    let target_language_arg: Arg = Arg::new("target-language")
+        .long("target-language")
+        .help(cformat!(
+            "{}\n{} --target-language en.",
+            localization.target_language_arg_desc,
+            localization.example,
+        ))
+        .value_name(localization.language_arg_type)
+        .required(true)
+        .display_order(8);
+
     let force_flag: Arg = Arg::new("force")
         .short('f')
         .long("force")
@@ -451,6 +748,42 @@ fn main() {
         .help(localization.append_arg_desc)
         .display_order(96);
 
+    let contextual_keys_flag: Arg = Arg::new("contextual-keys")
+        .long("contextual-keys")
+        .action(ArgAction::SetTrue)
+        .help(localization.contextual_keys_arg_desc)
+        .display_order(97);
+
+    let locations_flag: Arg = Arg::new("locations")
+        .long("locations")
+        .action(ArgAction::SetTrue)
+        .help(localization.locations_arg_desc)
+        .display_order(97);
+
+    let split_maps_flag: Arg = Arg::new("split-maps")
+        .long("split-maps")
+        .action(ArgAction::SetTrue)
+        .help(localization.split_maps_arg_desc)
+        .display_order(97);
+
+    let global_dedup_flag: Arg = Arg::new("global-dedup")
+        .long("global-dedup")
+        .action(ArgAction::SetTrue)
+        .help(localization.global_dedup_arg_desc)
+        .display_order(97);
+
+    let speaker_context_flag: Arg = Arg::new("speaker-context")
+        .long("speaker-context")
+        .action(ArgAction::SetTrue)
+        .help(localization.speaker_context_arg_desc)
+        .display_order(97);
+
+    let placeholders_flag: Arg = Arg::new("placeholders")
+        .long("placeholders")
+        .action(ArgAction::SetTrue)
+        .help(localization.placeholders_arg_desc)
+        .display_order(97);
+
     let disable_custom_processing_flag: Arg = Arg::new("disable-custom-processing")
         .long("disable-custom-processing")
         .action(ArgAction::SetTrue)
@@ -458,6 +791,42 @@ fn main() {
         .help(localization.disable_custom_processing_desc)
         .display_order(97);
 
+    let profile_arg: Arg = Arg::new("profile")
+        .long("profile")
+        .global(true)
+        .help(localization.profile_arg_desc)
+        .value_name(localization.profile_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(97);
+
+    let game_ids: Vec<String> = GameProfile::builtin()
+        .into_iter()
+        .map(|game_profile: GameProfile| game_profile.id)
+        .collect();
+
+    let game_arg: Arg = Arg::new("game")
+        .long("game")
+        .global(true)
+        .help(cformat!(
+            "{}\n{} --game {}.<bold>\n[{} {}]</bold>",
+            localization.game_arg_desc,
+            localization.example,
+            game_ids[0],
+            localization.possible_values,
+            game_ids.join(", "),
+        ))
+        .value_name(localization.game_arg_type)
+        .value_parser(value_parser!(String))
+        .display_order(97);
+
+    let rules_arg: Arg = Arg::new("rules")
+        .long("rules")
+        .global(true)
+        .help(localization.rules_arg_desc)
+        .value_name(localization.rules_arg_type)
+        .value_parser(value_parser!(PathBuf))
+        .display_order(97);
+
     let language_arg: Arg = Arg::new("language")
         .short('l')
         .long("language")
@@ -492,14 +861,82 @@ fn main() {
         .disable_help_flag(true)
         .help_template(localization.subcommand_help_template)
         .about(localization.read_command_desc)
-        .args([force_flag, append_flag, silent_flag])
+        .args([
+            force_flag,
+            append_flag,
+            contextual_keys_flag,
+            locations_flag,
+            split_maps_flag,
+            global_dedup_flag,
+            speaker_context_flag,
+            placeholders_flag,
+            silent_flag,
+        ])
         .arg(&help_flag);
 
     let write_subcommand: Command = Command::new("write")
         .disable_help_flag(true)
         .help_template(localization.subcommand_help_template)
         .about(localization.write_command_desc)
-        .args([shuffle_level_arg])
+        .args([shuffle_level_arg, wrap_width_arg, wrap_lines_arg])
+        .arg(&help_flag);
+
+    let export_xliff_subcommand: Command = Command::new("export-xliff")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.export_xliff_command_desc)
+        .args([&xliff_file_arg, &source_language_arg, &target_language_arg])
+        .arg(&help_flag);
+
+    let import_xliff_subcommand: Command = Command::new("import-xliff")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.import_xliff_command_desc)
+        .arg(&xliff_file_arg)
+        .arg(&help_flag);
+
+    let json_flag: Arg = Arg::new("json")
+        .long("json")
+        .action(ArgAction::SetTrue)
+        .help(localization.json_arg_desc)
+        .display_order(97);
+
+    let stats_subcommand: Command = Command::new("stats")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.stats_command_desc)
+        .arg(json_flag)
+        .arg(&help_flag);
+
+    let ignore_untranslated_flag: Arg = Arg::new("ignore-untranslated")
+        .long("ignore-untranslated")
+        .action(ArgAction::SetTrue)
+        .help(localization.ignore_untranslated_arg_desc)
+        .display_order(97);
+
+    let check_subcommand: Command = Command::new("check")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.check_command_desc)
+        .arg(ignore_untranslated_flag)
+        .arg(&help_flag);
+
+    let verify_subcommand: Command = Command::new("verify")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.verify_command_desc)
+        .arg(&help_flag);
+
+    let decrypt_assets_subcommand: Command = Command::new("decrypt-assets")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.decrypt_assets_command_desc)
+        .arg(&help_flag);
+
+    let encrypt_assets_subcommand: Command = Command::new("encrypt-assets")
+        .disable_help_flag(true)
+        .help_template(localization.subcommand_help_template)
+        .about(localization.encrypt_assets_command_desc)
         .arg(&help_flag);
 
     let cli: Command = Command::new("")
@@ -510,14 +947,33 @@ fn main() {
         .term_width(120)
         .about(localization.about_msg)
         .help_template(localization.help_template)
-        .subcommands([read_subcommand, write_subcommand])
+        .subcommands([
+            read_subcommand,
+            write_subcommand,
+            export_xliff_subcommand,
+            import_xliff_subcommand,
+            verify_subcommand,
+            stats_subcommand,
+            check_subcommand,
+            decrypt_assets_subcommand,
+            encrypt_assets_subcommand,
+        ])
         .args([
             input_dir_arg,
             output_dir_arg,
+            data_dir_arg,
+            plugins_file_arg,
+            translation_dir_arg,
             disable_processing_arg,
             romanize_arg,
+            format_arg,
+            enable_codes_arg,
+            disable_codes_arg,
             language_arg,
             disable_custom_processing_flag,
+            profile_arg,
+            game_arg,
+            rules_arg,
             log_flag,
             help_flag,
         ])
@@ -526,95 +982,122 @@ fn main() {
     let matches: ArgMatches = cli.get_matches();
     let (subcommand, subcommand_matches): (&str, &ArgMatches) = matches.subcommand().unwrap();
 
-    let (disable_maps_processing, disable_other_processing, disable_system_processing, disable_plugins_processing) =
-        matches
-            .get_many::<&str>("disable-processing")
-            .map(|disable_processing_args| {
-                let mut flags = (false, false, false, false);
-
-                for disable_processing_of in disable_processing_args {
-                    match *disable_processing_of {
-                        "maps" => flags.0 = true,
-                        "other" => flags.1 = true,
-                        "system" => flags.2 = true,
-                        "plugins" => flags.3 = true,
-                        _ => {}
-                    }
-                }
-                flags
-            })
-            .unwrap_or((false, false, false, false));
+    // Sections of the game, which files are processed
+    let sections: Vec<Section> = match matches.get_many::<String>("disable-processing") {
+        Some(disable_processing_args) => {
+            let disabled_sections: Vec<&str> = disable_processing_args.map(String::as_str).collect();
+
+            Section::ALL
+                .into_iter()
+                .zip(["maps", "other", "system", "plugins"])
+                .filter(|(_, name): &(Section, &str)| !disabled_sections.contains(name))
+                .map(|(section, _): (Section, &str)| section)
+                .collect()
+        }
+        None => Section::ALL.to_vec(),
+    };
 
     let logging: bool = matches.get_flag("log");
-    let disable_custom_processing: bool = matches.get_flag("disable-custom-processing");
-    let mut romanize: bool = matches.get_flag("romanize");
 
-    let input_dir: &Path = matches.get_one::<PathBuf>("input-dir").unwrap();
+    let mut options: ProjectOptions = ProjectOptions {
+        romanize: matches.get_flag("romanize"),
+        disable_custom_processing: matches.get_flag("disable-custom-processing"),
+        format: match matches
+            .get_one::<String>("format")
+            .map(|format: &String| format.as_str())
+        {
+            Some("po") => Format::Po,
+            _ => Format::Txt,
+        },
+        data_dir: matches.get_one::<PathBuf>("data-dir").cloned(),
+        plugins_file: matches.get_one::<PathBuf>("plugins-file").cloned(),
+        translation_dir: matches.get_one::<PathBuf>("translation-dir").cloned(),
+        game: matches.get_one::<String>("game").cloned(),
+        profile: matches.get_one::<PathBuf>("profile").cloned(),
+        rules: matches.get_one::<PathBuf>("rules").cloned(),
+        format_specified: matches.value_source("format") == Some(ValueSource::CommandLine),
+        codes_specified: matches.value_source("enable-codes") == Some(ValueSource::CommandLine)
+            || matches.value_source("disable-codes") == Some(ValueSource::CommandLine),
+        ..ProjectOptions::default()
+    };
 
-    if !input_dir.exists() {
-        panic!("{}", localization.input_dir_not_exist);
+    // Codes of the event commands, which text is extracted
+    if let Some(enabled_codes) = matches.get_many::<u64>("enable-codes") {
+        for code in enabled_codes {
+            if !options.codes.contains(code) {
+                options.codes.push(*code);
+            }
+        }
     }
 
-    let output_dir: &Path = matches.get_one::<PathBuf>("output-dir").unwrap();
-
-    if !output_dir.exists() {
-        panic!("{}", localization.output_dir_not_exist)
+    if let Some(disabled_codes) = matches.get_many::<u64>("disable-codes") {
+        let disabled_codes: Vec<&u64> = disabled_codes.collect();
+        options.codes.retain(|code: &u64| !disabled_codes.contains(&code));
     }
 
-    let mut original_path: PathBuf = input_dir.join("original");
-
-    if !original_path.exists() {
-        original_path = input_dir.join("data");
-
-        if !original_path.exists() {
-            panic!("{}", localization.original_dir_missing)
-        }
-    }
+    let input_dir: &Path = matches.get_one::<PathBuf>("input-dir").unwrap();
+    let output_dir: &Path = matches.get_one::<PathBuf>("output-dir").unwrap();
 
-    let (maps_path, other_path, metadata_file_path) = if output_dir.as_os_str().as_encoded_bytes() == "./".as_bytes() {
-        (
-            input_dir.join("translation/maps"),
-            input_dir.join("translation/other"),
-            input_dir.join("translation/.rvpacker-json-txt-metadata.json"),
-        )
+    // Translation and output folders are created in the input directory, unless the output directory is specified
+    let root_dir: &Path = if output_dir.as_os_str().as_encoded_bytes() == "./".as_bytes() {
+        input_dir
     } else {
-        (
-            output_dir.join("translation/maps"),
-            output_dir.join("translation/other"),
-            output_dir.join("translation/.rvpacker-json-txt-metadata.json"),
-        )
+        output_dir
     };
 
-    let system_file_path: PathBuf = original_path.join("System.json");
-
-    let mut game_type: Option<GameType> = if disable_custom_processing {
-        None
-    } else {
-        get_game_type(&system_file_path)
+    let mut project: Project = match Project::new(input_dir, root_dir, options) {
+        Ok(project) => project,
+        Err(error) => exit_with_error(project_error_msg(
+            &error,
+            input_dir,
+            matches.contains_id("data-dir"),
+            &localization,
+        )),
     };
 
-    if game_type.is_some() {
+    if logging {
+        project.messages = Some(ProjectMessages {
+            file_parsed: localization.file_parsed_msg,
+            file_already_parsed: localization.file_already_parsed_msg,
+            file_is_not_parsed: localization.file_is_not_parsed_msg,
+            file_written: localization.file_written_msg,
+            file_verified: localization.file_verified_msg,
+            placeholders_mismatch: localization.placeholders_mismatch_msg,
+        });
+    }
+
+    // Custom processing only affects the commands, that process game files
+    if project.game_profile().is_some() && matches!(subcommand, "read" | "write" | "verify") {
         println!("{}", localization.custom_processing_enabled_msg);
     }
 
+    let translation_path: PathBuf = project.translation_path().to_path_buf();
+    let translation_dirs_exist: bool =
+        translation_path.join("maps").exists() && translation_path.join("other").exists();
+
     let mut wait_time: f64 = 0f64;
+    let mut errors: Vec<Error> = Vec::new();
 
     if subcommand == "read" {
-        use read::*;
-
         let force: bool = subcommand_matches.get_flag("force");
         let append: bool = subcommand_matches.get_flag("append");
         let silent: bool = subcommand_matches.get_flag("silent");
 
-        let processing_type: ProcessingMode = if force {
+        let options: &mut ProjectOptions = &mut project.options;
+        options.contextual_keys = subcommand_matches.get_flag("contextual-keys");
+        options.locations = subcommand_matches.get_flag("locations");
+        options.split_maps = subcommand_matches.get_flag("split-maps");
+        options.global_dedup = subcommand_matches.get_flag("global-dedup");
+        options.speaker_context = subcommand_matches.get_flag("speaker-context");
+        options.placeholders = subcommand_matches.get_flag("placeholders");
+
+        options.processing_mode = if force {
             if !silent {
                 let start_time: Instant = Instant::now();
                 println!("{}", localization.force_mode_warning);
 
                 let mut buf: String = String::new();
-                stdin().read_line(&mut buf).unwrap();
-
-                if buf.trim_end() != "Y" {
+                if stdin().read_line(&mut buf).is_err() || buf.trim_end() != "Y" {
                     exit(0);
                 }
 
@@ -628,146 +1111,183 @@ fn main() {
             ProcessingMode::Default
         };
 
-        create_dir_all(&maps_path).unwrap();
-        create_dir_all(&other_path).unwrap();
-
-        write(
-            metadata_file_path,
-            format!(r#"{{"romanize":{romanize},"disableCustomProcessing":{disable_custom_processing}}}"#),
-        )
-        .unwrap();
-
-        if !disable_maps_processing {
-            read_map(
-                &original_path,
-                &maps_path,
-                romanize,
-                logging,
-                localization.file_parsed_msg,
-                localization.file_already_parsed_msg,
-                localization.file_is_not_parsed_msg,
-                &game_type,
-                &processing_type,
-            );
+        // Appending is only possible with the same layout of translation files, so options of the previous read are used
+        print_metadata_options(&project.apply_metadata(false), &localization);
+        errors.extend(project.read(&sections).err().unwrap_or_default());
+    } else if subcommand == "write" {
+        if !translation_dirs_exist {
+            exit_with_error(localization.translation_dirs_missing);
         }
 
-        if !disable_other_processing {
-            read_other(
-                &original_path,
-                &other_path,
-                romanize,
-                logging,
-                localization.file_parsed_msg,
-                localization.file_already_parsed_msg,
-                localization.file_is_not_parsed_msg,
-                &game_type,
-                &processing_type,
-            );
+        let options: &mut ProjectOptions = &mut project.options;
+        options.shuffle_level = *subcommand_matches.get_one::<u8>("shuffle-level").unwrap();
+        options.wrap_width = subcommand_matches
+            .get_one::<u16>("wrap-width")
+            .map(|width: &u16| *width as usize);
+        options.wrap_lines = *subcommand_matches.get_one::<u16>("wrap-lines").unwrap() as usize;
+
+        if options.shuffle_level > 0 {
+            seed(69);
         }
 
-        if !disable_system_processing {
-            read_system(
-                &system_file_path,
-                &other_path,
-                romanize,
-                logging,
-                localization.file_parsed_msg,
-                localization.file_already_parsed_msg,
-                localization.file_is_not_parsed_msg,
-                &processing_type,
-            );
+        print_metadata_options(&project.apply_metadata(true), &localization);
+        errors.extend(project.write(&sections).err().unwrap_or_default());
+    } else if subcommand == "verify" {
+        // Verification uses the same options as writing of the existing translation
+        print_metadata_options(&project.apply_metadata(true), &localization);
+
+        let (differences_count, verify_errors): (usize, Vec<Error>) = project.verify(&sections);
+        errors.extend(verify_errors);
+
+        if differences_count > 0 {
+            println!("{differences_count} {}", localization.differences_found_msg);
+            print_errors(&errors, localization.failures_msg);
+            exit(1);
         }
-    } else {
-        use write::*;
 
-        if !maps_path.exists() || !other_path.exists() {
-            panic!("{}", localization.translation_dirs_missing);
+        println!("{}", localization.no_differences_msg);
+    } else if subcommand == "stats" {
+        use stats::*;
+
+        if !translation_dirs_exist {
+            exit_with_error(localization.translation_dirs_missing);
         }
 
-        let plugins_path: PathBuf = input_dir.join("translation/plugins");
+        let json: bool = subcommand_matches.get_flag("json");
 
-        let (output_path, plugins_output_path) = if output_dir.as_os_str().as_encoded_bytes() == "./".as_bytes() {
-            (input_dir.join("output/data"), input_dir.join("output/js"))
-        } else {
-            (output_dir.join("output/data"), output_dir.join("output/js"))
-        };
+        // Only the format of the previous read is used, and the report is the only output in JSON mode
+        let changed_options: Vec<MetadataOption> = project
+            .apply_metadata(true)
+            .unwrap_or_else(|error: Error| exit_with_error(error));
 
-        create_dir_all(&output_path).unwrap();
-        create_dir_all(&plugins_output_path).unwrap();
+        if !json && changed_options.contains(&MetadataOption::PoFormat) {
+            println!("{}", localization.enabling_po_format_metadata_msg);
+        }
 
-        let shuffle_level: u8 = *subcommand_matches.get_one::<u8>("shuffle-level").unwrap();
+        errors.extend(
+            stats(
+                &translation_path,
+                project.options.format,
+                json,
+                &localization.stats_columns,
+                localization.stats_total,
+            )
+            .err(),
+        );
+
+        // Nothing else is printed, so the report can be parsed
+        if json && errors.is_empty() {
+            return;
+        }
+    } else if subcommand == "check" {
+        use check::*;
 
-        if shuffle_level > 0 {
-            seed(69);
+        if !translation_dirs_exist {
+            exit_with_error(localization.translation_dirs_missing);
         }
 
-        if metadata_file_path.exists() {
-            let metadata: Object = from_str(&read_to_string(metadata_file_path).unwrap()).unwrap();
+        let changed_options: Vec<MetadataOption> = project
+            .apply_metadata(true)
+            .unwrap_or_else(|error: Error| exit_with_error(error));
 
-            let romanize_bool: bool = metadata["romanize"].as_bool().unwrap();
-            let disable_custom_processing_bool: bool = metadata["disableCustomProcessing"].as_bool().unwrap();
+        if changed_options.contains(&MetadataOption::PoFormat) {
+            println!("{}", localization.enabling_po_format_metadata_msg);
+        }
 
-            if romanize_bool {
-                println!("{}", localization.enabling_romanize_metadata_msg);
-                romanize = romanize_bool;
+        let problems_count: usize = match check(
+            &translation_path,
+            project.options.format,
+            subcommand_matches.get_flag("ignore-untranslated"),
+            localization.lines_count_mismatch_msg,
+            localization.escape_codes_mismatch_msg,
+            localization.line_breaks_mismatch_msg,
+            localization.trailing_whitespace_msg,
+            localization.untranslated_msg,
+        ) {
+            Ok(problems_count) => problems_count,
+            Err(error) => {
+                errors.push(error);
+                0
             }
+        };
 
-            if disable_custom_processing_bool && game_type.is_some() {
-                println!("{}", localization.disabling_custom_processing_metadata_msg);
-                game_type = None;
-            }
+        if problems_count > 0 {
+            println!("{} {problems_count}", localization.problems_found_msg);
+            print_errors(&errors, localization.failures_msg);
+            exit(1);
         }
 
-        if !disable_maps_processing {
-            write_maps(
-                &maps_path,
-                &original_path,
-                &output_path,
-                romanize,
-                shuffle_level,
-                logging,
-                localization.file_written_msg,
-                &game_type,
-            );
+        println!("{}", localization.no_problems_msg);
+    } else if subcommand == "decrypt-assets" || subcommand == "encrypt-assets" {
+        use assets::*;
+
+        let game_path: PathBuf = find_game_path(input_dir);
+        let assets_path: PathBuf = translation_path.join("assets");
+
+        match encryption_key(&project.original_path().join("System.json")) {
+            Ok(key) if subcommand == "decrypt-assets" => {
+                errors.extend(decrypt_assets(
+                    &game_path,
+                    &assets_path,
+                    &key,
+                    logging,
+                    localization.file_decrypted_msg,
+                ));
+            }
+            Ok(key) => {
+                errors.extend(encrypt_assets(
+                    &assets_path,
+                    &game_path,
+                    project.output_path(),
+                    &key,
+                    logging,
+                    localization.file_encrypted_msg,
+                ));
+            }
+            Err(error) => errors.push(error),
         }
+    } else {
+        use xliff::*;
 
-        if !disable_other_processing {
-            write_other(
-                &other_path,
-                &original_path,
-                &output_path,
-                romanize,
-                shuffle_level,
-                logging,
-                localization.file_written_msg,
-                &game_type,
-            );
+        if !translation_dirs_exist {
+            exit_with_error(localization.translation_dirs_missing);
         }
 
-        if !disable_system_processing {
-            write_system(
-                &system_file_path,
-                &other_path,
-                &output_path,
-                romanize,
-                shuffle_level,
-                logging,
-                localization.file_written_msg,
-            );
-        }
+        let xliff_file_path: PathBuf = subcommand_matches
+            .get_one::<PathBuf>("xliff-file")
+            .cloned()
+            .unwrap_or_else(|| translation_path.join("translation.xlf"));
 
-        if !disable_plugins_processing
-            && plugins_path.exists()
-            && game_type.is_some()
-            && game_type.unwrap() == GameType::Termina
-        {
-            write_plugins(
-                &plugins_path.join("plugins.json"),
-                &plugins_path,
-                &plugins_output_path,
-                shuffle_level,
-                logging,
-                localization.file_written_msg,
+        if subcommand == "export-xliff" {
+            let changed_options: Vec<MetadataOption> = project
+                .apply_metadata(true)
+                .unwrap_or_else(|error: Error| exit_with_error(error));
+
+            if changed_options.contains(&MetadataOption::PoFormat) {
+                println!("{}", localization.enabling_po_format_metadata_msg);
+            }
+
+            errors.extend(
+                export_xliff(
+                    &translation_path,
+                    &xliff_file_path,
+                    subcommand_matches.get_one::<String>("source-language").unwrap(),
+                    subcommand_matches.get_one::<String>("target-language").unwrap(),
+                    logging,
+                    localization.file_exported_msg,
+                    project.options.format,
+                )
+                .err(),
+            );
+        } else {
+            errors.extend(
+                import_xliff(
+                    &xliff_file_path,
+                    &translation_path,
+                    logging,
+                    localization.file_written_msg,
+                )
+                .err(),
             );
         }
     }
@@ -777,4 +1297,9 @@ fn main() {
         localization.done_in_msg,
         start_time.elapsed().as_secs_f64() - wait_time
     );
+
+    if !errors.is_empty() {
+        print_errors(&errors, localization.failures_msg);
+        exit(1);
+    }
 }
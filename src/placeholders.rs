@@ -0,0 +1,194 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Match, Regex};
+
+lazy_static! {
+    // RPG Maker escape codes, like \C[n], \N[n], \V[n], \I[n], \G, \{, \., \| and \\, including the ones added by
+    // plugins. \# is not included, since it's a line break of translation files.
+    pub static ref ESCAPE_CODE_RE: Regex = Regex::new(r"\\(?:[A-Za-z]+(?:\[[^\]\\]*\])?|[{}\\$.|!<>^])").unwrap();
+
+    // Escape codes with the text argument in angle brackets (e.g. name box \n<Name> of Yanfly's message core), escape
+    // codes and plugin tags, like <WordWrap> or <br>
+    static ref CONTROL_SEQUENCE_RE: Regex = Regex::new(
+        r"(\\[A-Za-z]+)<[^<>\\]*>|\\(?:([A-Za-z]+)(?:\[([^\]\\]*)\])?|([{}\\$.|!<>^]))|<(/?[A-Za-z][A-Za-z0-9_]*)>"
+    )
+    .unwrap();
+
+    static ref PLACEHOLDER_RE: Regex = Regex::new(r"\{[^{}\s]+\}").unwrap();
+}
+
+/// Returns the name of the placeholder for escape code, that consists of a single symbol.
+fn symbol_placeholder_name(symbol: &str) -> &'static str {
+    match symbol {
+        "{" => "BIGGER",
+        "}" => "SMALLER",
+        "\\" => "BACKSLASH",
+        "$" => "GOLD",
+        "." => "WAIT",
+        "|" => "WAIT_LONG",
+        "!" => "PAUSE",
+        ">" => "INSTANT_ON",
+        "<" => "INSTANT_OFF",
+        _ => "NO_WAIT",
+    }
+}
+
+/// Replaces escape codes and plugin tags of the text with placeholders, e.g. `\C[2]` with `{C2}`, `\.` with `{WAIT}`
+/// and `<WordWrap>` with `{WordWrap}`, so they can't be broken by translators. Text argument of escape codes in angle
+/// brackets, like `\n<Name>`, is left as is, since it may need translation. Returns the protected text and the pairs of
+/// placeholders and control sequences they replace, in order of their occurrence. If the same placeholder would
+/// replace different sequences, or the text already contains it, the sequence isn't protected, so it's restored
+/// unambiguously.
+pub fn protect_codes(text: &str) -> (String, Vec<(String, String)>) {
+    let mut placeholders: Vec<(String, String)> = Vec::new();
+
+    let protected: String = CONTROL_SEQUENCE_RE
+        .replace_all(text, |captures: &Captures| {
+            let whole: &str = captures.get(0).unwrap().as_str();
+
+            let (code, name, rest): (&str, String, &str) = if let Some(code) = captures.get(1) {
+                (code.as_str(), code.as_str()[1..].to_string(), &whole[code.len()..])
+            } else if let Some(letters) = captures.get(2) {
+                let argument: &str = captures
+                    .get(3)
+                    .map(|argument: Match| argument.as_str())
+                    .unwrap_or_default();
+                (whole, format!("{}{argument}", letters.as_str()), "")
+            } else if let Some(symbol) = captures.get(4) {
+                (whole, symbol_placeholder_name(symbol.as_str()).to_string(), "")
+            } else {
+                (whole, captures[5].to_string(), "")
+            };
+
+            let placeholder: String = format!("{{{name}}}");
+
+            if text.contains(&placeholder)
+                || placeholders
+                    .iter()
+                    .any(|(existing, existing_code)| *existing == placeholder && existing_code != code)
+            {
+                return whole.to_string();
+            }
+
+            placeholders.push((placeholder.clone(), code.to_string()));
+            placeholder + rest
+        })
+        .into_owned();
+
+    (protected, placeholders)
+}
+
+// Returns sorted placeholders of the translation, that are present in expected placeholders
+fn find_placeholders<'a>(translated: &'a str, expected: &[&str]) -> Vec<&'a str> {
+    let mut found: Vec<&str> = PLACEHOLDER_RE
+        .find_iter(translated)
+        .map(|placeholder: Match| placeholder.as_str())
+        .filter(|placeholder: &&str| expected.contains(placeholder))
+        .collect();
+
+    found.sort_unstable();
+    found
+}
+
+/// Returns whether the translation contains the same placeholders as the protected original text.
+pub fn placeholders_match(original: &str, translated: &str) -> bool {
+    let mut expected: Vec<&str> = PLACEHOLDER_RE
+        .find_iter(original)
+        .map(|placeholder: Match| placeholder.as_str())
+        .collect();
+    expected.sort_unstable();
+
+    find_placeholders(translated, &expected) == expected
+}
+
+/// Replaces placeholders of the translation with the control sequences they were made from. Returns None if the
+/// placeholders of the translation don't match the placeholders of the original text, as the translation would lose
+/// or duplicate some of the control sequences.
+pub fn restore_codes(translated: &str, placeholders: &[(String, String)]) -> Option<String> {
+    let mut expected: Vec<&str> = placeholders
+        .iter()
+        .map(|(placeholder, _)| placeholder.as_str())
+        .collect();
+    expected.sort_unstable();
+
+    if find_placeholders(translated, &expected) != expected {
+        return None;
+    }
+
+    let restored: String = PLACEHOLDER_RE
+        .replace_all(translated, |captures: &Captures| {
+            let placeholder: &str = captures.get(0).unwrap().as_str();
+
+            placeholders
+                .iter()
+                .find(|(existing, _)| existing == placeholder)
+                .map(|(_, code)| code.clone())
+                .unwrap_or_else(|| placeholder.to_string())
+        })
+        .into_owned();
+
+    Some(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_are_protected_and_restored() {
+        let (protected, placeholders) = protect_codes(r"\C[2]Hello\C[0]\. <WordWrap>\\");
+
+        assert_eq!(protected, "{C2}Hello{C0}{WAIT} {WordWrap}{BACKSLASH}");
+        assert_eq!(
+            restore_codes("{WordWrap}{C2}Hi{C0}{WAIT} {BACKSLASH}", &placeholders).as_deref(),
+            Some(r"<WordWrap>\C[2]Hi\C[0]\. \\")
+        );
+    }
+
+    #[test]
+    fn argument_of_name_box_is_left_translatable() {
+        let (protected, placeholders) = protect_codes(r"\n<Guard>Halt!");
+
+        assert_eq!(protected, "{n}<Guard>Halt!");
+        assert_eq!(
+            restore_codes("{n}<Wache>Halt!", &placeholders).as_deref(),
+            Some(r"\n<Wache>Halt!")
+        );
+    }
+
+    #[test]
+    fn colliding_placeholders_are_not_protected() {
+        // <WAIT> tag and \. escape code have the same placeholder, so only the first of them is replaced
+        let (protected, placeholders) = protect_codes(r"\.<WAIT>");
+
+        assert_eq!(protected, "{WAIT}<WAIT>");
+        assert_eq!(placeholders, [("{WAIT}".to_string(), r"\.".to_string())]);
+
+        // Text, that already contains the placeholder, can't be restored unambiguously
+        let (protected, placeholders) = protect_codes(r"{C2} \C[2]");
+
+        assert_eq!(protected, r"{C2} \C[2]");
+        assert!(placeholders.is_empty());
+    }
+
+    #[test]
+    fn translation_with_mismatched_placeholders_is_not_restored() {
+        let (protected, placeholders) = protect_codes(r"\C[2]Hello\C[2]");
+
+        assert_eq!(protected, "{C2}Hello{C2}");
+        assert_eq!(restore_codes("{C2}Hi", &placeholders), None);
+        assert_eq!(restore_codes("{C2}Hi{C2}{C2}", &placeholders), None);
+        assert_eq!(
+            restore_codes("{C2}Hi{C2} {unknown}", &placeholders).as_deref(),
+            Some(r"\C[2]Hi\C[2] {unknown}")
+        );
+    }
+
+    #[test]
+    fn placeholders_are_matched_by_count() {
+        assert!(placeholders_match("{C2}a{WAIT}", "{WAIT}b{C2}"));
+        assert!(placeholders_match("{C2}a", "{C2}b {other}"));
+        assert!(!placeholders_match("{C2}a{C2}", "{C2}b"));
+        assert!(!placeholders_match("{C2}a", "{C2}b{C2}"));
+        assert!(!placeholders_match("{C2}a", "b"));
+    }
+}
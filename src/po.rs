@@ -0,0 +1,390 @@
+use crate::CONTEXT_SEPARATOR;
+use std::{iter::Peekable, str::Chars};
+
+/// Single entry of a gettext .po file.
+pub struct PoEntry {
+    pub context: Option<String>,
+    pub original: String,
+    pub translated: String,
+    pub references: Vec<String>,
+    pub comments: Vec<String>,
+    /// Whether the translation is marked as fuzzy, so it needs review. Such translation isn't written to the game.
+    pub fuzzy: bool,
+    /// Number of the line of the file, where msgid of the entry starts, or 0 if the entry wasn't parsed from a file.
+    pub line: usize,
+}
+
+impl PoEntry {
+    /// Creates an entry from a translation map key, which is either a plain text or a location and a text, separated
+    /// by CONTEXT_SEPARATOR, as gettext does it.
+    pub fn from_key(key: String, translated: String, references: Vec<String>) -> Self {
+        let (context, original) = match key.split_once(CONTEXT_SEPARATOR) {
+            Some((context, original)) => (Some(context.to_string()), original.to_string()),
+            None => (None, key),
+        };
+
+        PoEntry {
+            context,
+            original,
+            translated,
+            references,
+            comments: Vec::new(),
+            fuzzy: false,
+            line: 0,
+        }
+    }
+
+    /// Returns translation map key of the entry.
+    pub fn key(&self) -> String {
+        match &self.context {
+            Some(context) => format!("{context}{CONTEXT_SEPARATOR}{}", self.original),
+            None => self.original.clone(),
+        }
+    }
+}
+
+// Translation lines use \# as a line break, while .po files use regular escaped line breaks. Backslashes are escaped
+// one by one, so a backslash, that precedes a line break, stays a backslash.
+fn escape(string: &str) -> String {
+    let mut escaped: String = String::with_capacity(string.len());
+    let mut chars: Peekable<Chars> = string.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\\' if chars.next_if_eq(&'#').is_some() => escaped.push_str(r"\n"),
+            '\\' => escaped.push_str(r"\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str(r"\n"),
+            '\r' => escaped.push_str(r"\r"),
+            '\t' => escaped.push_str(r"\t"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(string: &str) -> String {
+    let mut unescaped: String = String::with_capacity(string.len());
+    let mut chars: Chars = string.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            unescaped.push(char);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push_str(r"\#"),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+// Returns the content of quoted .po string
+fn unquote(string: &str) -> &str {
+    let string: &str = string.trim();
+    string
+        .strip_prefix('"')
+        .and_then(|string: &str| string.strip_suffix('"'))
+        .unwrap_or(string)
+}
+
+/// Parses .po file content into entries. Header entry and obsolete entries are skipped, and entries with the fuzzy
+/// flag are marked as such.
+/// # Parameters
+/// * `content` - content of .po file
+pub fn parse_po(content: &str) -> Vec<PoEntry> {
+    #[derive(PartialEq)]
+    enum Field {
+        None,
+        Context,
+        Original,
+        Translated,
+        Plural,
+    }
+
+    let mut entries: Vec<PoEntry> = Vec::new();
+
+    let mut context: Option<String> = None;
+    let mut original: Option<String> = None;
+    let mut translated: String = String::new();
+    let mut references: Vec<String> = Vec::new();
+    let mut comments: Vec<String> = Vec::new();
+    let mut fuzzy: bool = false;
+    let mut field: Field = Field::None;
+    let mut original_line: usize = 0;
+
+    let mut flush = |context: &mut Option<String>,
+                     original: &mut Option<String>,
+                     translated: &mut String,
+                     references: &mut Vec<String>,
+                     comments: &mut Vec<String>,
+                     fuzzy: &mut bool,
+                     line: usize| {
+        if let Some(original) = original.take() {
+            if !original.is_empty() || context.is_some() {
+                entries.push(PoEntry {
+                    context: context.take().map(|context: String| unescape(&context)),
+                    original: unescape(&original),
+                    translated: unescape(translated),
+                    references: std::mem::take(references),
+                    comments: std::mem::take(comments)
+                        .iter()
+                        .map(|comment: &String| unescape(comment))
+                        .collect(),
+                    fuzzy: *fuzzy,
+                    line,
+                });
+            }
+        }
+
+        *context = None;
+        translated.clear();
+        references.clear();
+        comments.clear();
+        *fuzzy = false;
+    };
+
+    for (i, line) in content.lines().enumerate() {
+        let line: &str = line.trim();
+
+        if line.is_empty() || line.starts_with("#~") {
+            continue;
+        }
+
+        if let Some(reference) = line.strip_prefix("#:") {
+            if field == Field::Translated {
+                flush(
+                    &mut context,
+                    &mut original,
+                    &mut translated,
+                    &mut references,
+                    &mut comments,
+                    &mut fuzzy,
+                    original_line,
+                );
+                field = Field::None;
+            }
+
+            references.push(reference.trim().to_string());
+        } else if let Some(comment) = line.strip_prefix("#.") {
+            if field == Field::Translated {
+                flush(
+                    &mut context,
+                    &mut original,
+                    &mut translated,
+                    &mut references,
+                    &mut comments,
+                    &mut fuzzy,
+                    original_line,
+                );
+                field = Field::None;
+            }
+
+            comments.push(comment.trim().to_string());
+        } else if line.starts_with('#') {
+            if field == Field::Translated {
+                flush(
+                    &mut context,
+                    &mut original,
+                    &mut translated,
+                    &mut references,
+                    &mut comments,
+                    &mut fuzzy,
+                    original_line,
+                );
+                field = Field::None;
+            }
+
+            // Flags precede the entry they belong to
+            if let Some(flags) = line.strip_prefix("#,") {
+                fuzzy |= flags.split(',').any(|flag: &str| flag.trim() == "fuzzy");
+            }
+        } else if let Some(string) = line.strip_prefix("msgctxt") {
+            if field == Field::Translated {
+                flush(
+                    &mut context,
+                    &mut original,
+                    &mut translated,
+                    &mut references,
+                    &mut comments,
+                    &mut fuzzy,
+                    original_line,
+                );
+            }
+
+            context = Some(unquote(string).to_string());
+            field = Field::Context;
+        } else if line.starts_with("msgid_plural") {
+            field = Field::Plural;
+        } else if let Some(string) = line.strip_prefix("msgid") {
+            if field == Field::Translated {
+                flush(
+                    &mut context,
+                    &mut original,
+                    &mut translated,
+                    &mut references,
+                    &mut comments,
+                    &mut fuzzy,
+                    original_line,
+                );
+            }
+
+            original = Some(unquote(string).to_string());
+            original_line = i + 1;
+            field = Field::Original;
+        } else if let Some(string) = line.strip_prefix("msgstr") {
+            // Plural forms aren't used by RPG Maker, so only the first form is taken
+            let string: &str = string.trim_start();
+
+            if string.starts_with("[0]") || string.starts_with('"') {
+                translated.push_str(unquote(string.trim_start_matches("[0]")));
+                field = Field::Translated;
+            } else {
+                field = Field::Plural;
+            }
+        } else if line.starts_with('"') {
+            let string: &str = unquote(line);
+
+            match field {
+                Field::Context => context.get_or_insert_with(String::new).push_str(string),
+                Field::Original => original.get_or_insert_with(String::new).push_str(string),
+                Field::Translated => translated.push_str(string),
+                Field::None | Field::Plural => {}
+            }
+        }
+    }
+
+    flush(
+        &mut context,
+        &mut original,
+        &mut translated,
+        &mut references,
+        &mut comments,
+        &mut fuzzy,
+        original_line,
+    );
+    entries
+}
+
+/// Serializes entries to .po file content.
+/// # Parameters
+/// * `entries` - entries to serialize
+pub fn to_po_string(entries: &[PoEntry]) -> String {
+    let mut content: String =
+        String::from("msgid \"\"\nmsgstr \"\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\"Content-Transfer-Encoding: 8bit\\n\"\n");
+
+    for entry in entries {
+        content.push('\n');
+
+        // Comments are escaped as strings, so line breaks in them don't break the file
+        for comment in &entry.comments {
+            content.push_str("#. ");
+            content.push_str(&escape(comment));
+            content.push('\n');
+        }
+
+        for reference in &entry.references {
+            content.push_str("#: ");
+            content.push_str(reference);
+            content.push('\n');
+        }
+
+        if entry.fuzzy {
+            content.push_str("#, fuzzy\n");
+        }
+
+        if let Some(context) = &entry.context {
+            content.push_str(&format!("msgctxt \"{}\"\n", escape(context)));
+        }
+
+        content.push_str(&format!("msgid \"{}\"\n", escape(&entry.original)));
+        content.push_str(&format!("msgstr \"{}\"\n", escape(&entry.translated)));
+    }
+
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PO: &str = r#"msgid ""
+msgstr ""
+"Content-Type: text/plain; charset=UTF-8\n"
+
+#. Guard:
+#: Map001.json:1:0:2
+msgid "Hello,\n"
+"\"stranger\"!"
+msgstr ""
+"Привет,\n"
+"«незнакомец»!"
+
+msgctxt "Map001.json:1:0:5"
+msgid "Hello"
+msgstr "Hi"
+
+#, fuzzy
+msgid "Bye"
+msgstr "Пока"
+
+#~ msgid "Obsolete"
+#~ msgstr "Устарело"
+"#;
+
+    #[test]
+    fn entries_are_parsed() {
+        let entries: Vec<PoEntry> = parse_po(PO);
+
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].original, r#"Hello,\#"stranger"!"#);
+        assert_eq!(entries[0].translated, r"Привет,\#«незнакомец»!");
+        assert_eq!(entries[0].comments, ["Guard:"]);
+        assert_eq!(entries[0].references, ["Map001.json:1:0:2"]);
+        assert_eq!(entries[0].line, 7);
+
+        assert_eq!(entries[1].key(), format!("Map001.json:1:0:5{CONTEXT_SEPARATOR}Hello"));
+        assert_eq!(entries[1].translated, "Hi");
+        assert!(!entries[1].fuzzy);
+
+        assert_eq!(entries[2].original, "Bye");
+        assert!(entries[2].fuzzy);
+    }
+
+    #[test]
+    fn entries_are_written_back_unchanged() {
+        let mut entry: PoEntry = PoEntry::from_key(
+            format!("Map001.json:1:0:5{CONTEXT_SEPARATOR}Path C:\\\\#\"quoted\"\ttab"),
+            r"Путь C:\\#«в кавычках»".into(),
+            vec!["Map001.json:1:0:5".into()],
+        );
+        entry.comments = vec![r"\N[1]:\#second line".into()];
+        entry.fuzzy = true;
+
+        let content: String = to_po_string(&[entry]);
+
+        // Line breaks are escaped, so every line of the file is a single field or comment
+        assert!(content.contains("#. \\\\N[1]:\\nsecond line\n"));
+        assert!(content.contains("#, fuzzy\n"));
+
+        let entries: Vec<PoEntry> = parse_po(&content);
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].context.as_deref(), Some("Map001.json:1:0:5"));
+        assert_eq!(entries[0].original, "Path C:\\\\#\"quoted\"\ttab");
+        assert_eq!(entries[0].translated, r"Путь C:\\#«в кавычках»");
+        assert_eq!(entries[0].comments, [r"\N[1]:\#second line"]);
+        assert_eq!(entries[0].references, ["Map001.json:1:0:5"]);
+        assert!(entries[0].fuzzy);
+
+        assert_eq!(to_po_string(&entries), content);
+    }
+}
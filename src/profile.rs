@@ -0,0 +1,559 @@
+use crate::{
+    error::{parse_json, read_file, Error},
+    rules::NoteTags,
+    Code, Variable,
+};
+use regex::Regex;
+use sonic_rs::{JsonContainerTrait, JsonValueTrait, Value};
+use std::{
+    fs::read,
+    path::{Path, PathBuf},
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+// Profiles of the games, that are processed with custom processing out of the box
+const BUILTIN_PROFILES: [&str; 1] = [include_str!("profiles/termina.json")];
+
+/// Set of string matchers. A string matches, if it matches any of the matchers.
+#[derive(Default)]
+struct Matcher {
+    all: bool,
+    equals: Vec<String>,
+    starts_with: Vec<String>,
+    ends_with: Vec<String>,
+    contains: Vec<String>,
+    regexes: Vec<Regex>,
+}
+
+impl Matcher {
+    /// Parses the matcher from either `true`, that matches every string, or an object with `equals`, `starts_with`,
+    /// `ends_with`, `contains` and `regex` arrays of strings.
+    fn parse(value: &Value) -> Result<Self, String> {
+        if value.as_bool() == Some(true) {
+            return Ok(Matcher {
+                all: true,
+                ..Default::default()
+            });
+        }
+
+        if !value.is_object() {
+            return Err(String::from("rule must be true or an object"));
+        }
+
+        let regexes: Vec<Regex> = strings(value.get("regex"))?
+            .iter()
+            .map(|regex: &String| Regex::new(regex).map_err(|error: regex::Error| error.to_string()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Matcher {
+            all: false,
+            equals: strings(value.get("equals"))?,
+            starts_with: strings(value.get("starts_with"))?,
+            ends_with: strings(value.get("ends_with"))?,
+            contains: strings(value.get("contains"))?,
+            regexes,
+        })
+    }
+
+    fn matches(&self, string: &str) -> bool {
+        self.all
+            || self.equals.iter().any(|equal: &String| string == equal)
+            || self
+                .starts_with
+                .iter()
+                .any(|prefix: &String| string.starts_with(prefix))
+            || self.ends_with.iter().any(|suffix: &String| string.ends_with(suffix))
+            || self.contains.iter().any(|part: &String| string.contains(part))
+            || self.regexes.iter().any(|regex: &Regex| regex.is_match(string))
+    }
+}
+
+/// Filter of strings, that applies to the files, which names start with one of the prefixes, or to every file, if
+/// there's no prefixes. Strings, that match `skip`, or don't match `allow`, are skipped.
+struct Filter {
+    files: Vec<String>,
+    allow: Option<Matcher>,
+    skip: Option<Matcher>,
+}
+
+impl Filter {
+    fn parse(value: &Value) -> Result<Self, String> {
+        Ok(Filter {
+            files: strings(value.get("files"))?,
+            allow: value.get("allow").map(Matcher::parse).transpose()?,
+            skip: value.get("skip").map(Matcher::parse).transpose()?,
+        })
+    }
+
+    fn applies_to(&self, filename: &str) -> bool {
+        self.files.is_empty() || self.files.iter().any(|prefix: &String| filename.starts_with(prefix))
+    }
+
+    fn skips(&self, string: &str) -> bool {
+        self.skip.as_ref().is_some_and(|skip: &Matcher| skip.matches(string))
+            || self
+                .allow
+                .as_ref()
+                .is_some_and(|allow: &Matcher| !allow.matches(string))
+    }
+}
+
+/// Handling of notes of the games, that store the end of long descriptions at the start of the note. The first line of
+/// such note is parsed as the continuation of the description, and the rest of the note isn't parsed.
+pub struct NoteContinuation {
+    /// Prefixes of the files, which notes are parsed as is
+    skip_files: Vec<String>,
+    /// Prefixes of the notes, that never continue the description
+    ignored_prefixes: Vec<String>,
+    /// Prefixes of the notes, that always continue the description
+    forced_prefixes: Vec<String>,
+    /// Chars, that the continuation of the description ends with
+    terminators: Vec<char>,
+}
+
+impl NoteContinuation {
+    fn parse(value: &Value) -> Result<Self, String> {
+        Ok(NoteContinuation {
+            skip_files: strings(value.get("skip_files"))?,
+            ignored_prefixes: strings(value.get("ignored_prefixes"))?,
+            forced_prefixes: strings(value.get("forced_prefixes"))?,
+            terminators: value
+                .get("terminators")
+                .and_then(|terminators: &Value| terminators.as_str())
+                .unwrap_or(".!?")
+                .chars()
+                .collect(),
+        })
+    }
+
+    fn starts_continuation(&self, note: &str) -> bool {
+        if self
+            .ignored_prefixes
+            .iter()
+            .any(|prefix: &String| note.starts_with(prefix))
+        {
+            return false;
+        }
+
+        let mut note_chars: std::str::Chars = note.chars();
+
+        let (Some(first_char), Some(second_char)) = (note_chars.next(), note_chars.next()) else {
+            return false;
+        };
+
+        ((first_char == '\n' && second_char != '\n')
+            || first_char.is_ascii_alphabetic()
+            || first_char == '"'
+            || self
+                .forced_prefixes
+                .iter()
+                .any(|prefix: &String| note.starts_with(prefix)))
+            && !['.', '!', '/', '?'].contains(&first_char)
+    }
+
+    /// Returns the continuation of the description, if the note starts with it.
+    pub fn description_continuation<'a>(&self, note: &'a str) -> Option<&'a str> {
+        if !self.starts_continuation(note) {
+            return None;
+        }
+
+        let line: &str = match note.trim_start().split_once('\n') {
+            Some((left, _)) => left.trim(),
+            None => note,
+        };
+
+        line.ends_with(self.terminators.as_slice()).then_some(line)
+    }
+}
+
+/// Properties of the game, that identify it regardless of its title, which is translated along with the rest of the
+/// text. The game matches the fingerprint, if it has all of them.
+struct Fingerprint {
+    /// Values of `versionId` of System.json, one of which the game has
+    version_ids: Vec<u64>,
+    /// Names of the plugins, that the game has
+    plugins: Vec<String>,
+    /// Paths of the files relative to the game directory, that the game has, with xxh3 hashes of their content, if
+    /// they must match
+    files: Vec<(String, Option<u64>)>,
+}
+
+impl Fingerprint {
+    fn parse(value: &Value) -> Result<Self, String> {
+        let version_ids: Vec<u64> = match value.get("version_ids") {
+            Some(version_ids) => version_ids
+                .as_array()
+                .ok_or_else(|| String::from("expected an array of numbers"))?
+                .iter()
+                .map(|version_id: &Value| {
+                    version_id
+                        .as_u64()
+                        .ok_or_else(|| String::from("expected an array of numbers"))
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+
+        let files: Vec<(String, Option<u64>)> = match value.get("files") {
+            Some(files) => files
+                .as_object()
+                .ok_or_else(|| String::from("expected an object of file paths and hashes"))?
+                .iter()
+                .map(|(path, hash): (&str, &Value)| {
+                    let hash: Option<u64> = match hash.as_str() {
+                        Some(hash) => Some(u64::from_str_radix(hash, 16).map_err(|_| format!("invalid hash {hash}"))?),
+                        None => None,
+                    };
+
+                    Ok((path.to_string(), hash))
+                })
+                .collect::<Result<_, String>>()?,
+            None => Vec::new(),
+        };
+
+        let fingerprint: Fingerprint = Fingerprint {
+            version_ids,
+            plugins: strings(value.get("plugins"))?,
+            files,
+        };
+
+        if fingerprint.version_ids.is_empty() && fingerprint.plugins.is_empty() && fingerprint.files.is_empty() {
+            return Err(String::from("fingerprint must have version_ids, plugins or files"));
+        }
+
+        Ok(fingerprint)
+    }
+
+    fn matches(&self, game: &GameInfo) -> bool {
+        (self.version_ids.is_empty() || game.version_id.is_some_and(|id: u64| self.version_ids.contains(&id)))
+            && self.plugins.iter().all(|plugin: &String| game.plugins.contains(plugin))
+            && self.files.iter().all(|(path, hash)| {
+                let file_path: PathBuf = game.game_path.join(path);
+
+                match hash {
+                    Some(hash) => read(file_path).is_ok_and(|content: Vec<u8>| xxh3_64(&content) == *hash),
+                    None => file_path.exists(),
+                }
+            })
+    }
+}
+
+/// Properties of the game, that are matched against fingerprints of the profiles.
+pub struct GameInfo<'a> {
+    /// Value of `versionId` of System.json
+    pub version_id: Option<u64>,
+    /// Names of the plugins of plugins.js
+    pub plugins: Vec<String>,
+    /// Directory, that contains `data`, `js`, `img` and `audio` folders of the game
+    pub game_path: &'a Path,
+}
+
+/// Declarative description of custom processing of a game: which parsed strings are skipped, which plugins are
+/// parsed and how notes are handled.
+pub struct GameProfile {
+    pub id: String,
+    pub name: String,
+    fingerprint: Option<Fingerprint>,
+    parameters: Vec<(Vec<Code>, Filter)>,
+    variables: Vec<(Vec<Variable>, Filter)>,
+    plugins: Option<Vec<String>>,
+    note_continuation: Option<NoteContinuation>,
+    pub(crate) note_tags: Vec<NoteTags>,
+}
+
+/// Parses an optional array of strings.
+pub(crate) fn strings(value: Option<&Value>) -> Result<Vec<String>, String> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+
+    value
+        .as_array()
+        .ok_or_else(|| String::from("expected an array of strings"))?
+        .iter()
+        .map(|string: &Value| {
+            string
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| String::from("expected an array of strings"))
+        })
+        .collect()
+}
+
+/// Parses an optional array of objects with the parse function.
+pub(crate) fn objects<T>(value: Option<&Value>, parse: fn(&Value) -> Result<T, String>) -> Result<Vec<T>, String> {
+    let Some(value) = value else {
+        return Ok(Vec::new());
+    };
+
+    value
+        .as_array()
+        .ok_or_else(|| String::from("expected an array of objects"))?
+        .iter()
+        .map(parse)
+        .collect()
+}
+
+pub(crate) fn parse_code(name: &str) -> Result<Code, String> {
+    match name {
+        "speaker" => Ok(Code::Speaker),
+        "dialogue" => Ok(Code::Dialogue),
+        "choice" => Ok(Code::Choice),
+        "system" => Ok(Code::System),
+        "unknown" => Ok(Code::Unknown),
+        _ => Err(format!("unknown code kind {name}")),
+    }
+}
+
+pub(crate) fn parse_variable(name: &str) -> Result<Variable, String> {
+    match name {
+        "name" => Ok(Variable::Name),
+        "nickname" => Ok(Variable::Nickname),
+        "profile" => Ok(Variable::Profile),
+        "description" => Ok(Variable::Description),
+        "message1" => Ok(Variable::Message1),
+        "message2" => Ok(Variable::Message2),
+        "message3" => Ok(Variable::Message3),
+        "message4" => Ok(Variable::Message4),
+        "note" => Ok(Variable::Note),
+        _ => Err(format!("unknown variable {name}")),
+    }
+}
+
+impl GameProfile {
+    /// Parses the profile from its JSON content. Returns the description of the problem, if the profile is invalid.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let profile: Value = sonic_rs::from_str(content).map_err(|error: sonic_rs::Error| error.to_string())?;
+
+        let id: String = profile["id"]
+            .as_str()
+            .ok_or_else(|| String::from("profile must have an id"))?
+            .to_string();
+
+        let parameters: Vec<(Vec<Code>, Filter)> = objects(profile.get("parameters"), |rule: &Value| {
+            let codes: Vec<Code> = strings(rule.get("codes"))?
+                .iter()
+                .map(|code: &String| parse_code(code))
+                .collect::<Result<_, _>>()?;
+
+            Ok((codes, Filter::parse(rule)?))
+        })?;
+
+        let variables: Vec<(Vec<Variable>, Filter)> = objects(profile.get("variables"), |rule: &Value| {
+            let variables: Vec<Variable> = strings(rule.get("variables"))?
+                .iter()
+                .map(|variable: &String| parse_variable(variable))
+                .collect::<Result<_, _>>()?;
+
+            Ok((variables, Filter::parse(rule)?))
+        })?;
+
+        let notes: Option<&Value> = profile.get("notes");
+
+        Ok(GameProfile {
+            name: profile["name"].as_str().unwrap_or(&id).to_string(),
+            id,
+            fingerprint: profile.get("fingerprint").map(Fingerprint::parse).transpose()?,
+            parameters,
+            variables,
+            plugins: profile
+                .get("plugins")
+                .map(|plugins: &Value| strings(Some(plugins)))
+                .transpose()?,
+            note_continuation: notes
+                .and_then(|notes: &Value| notes.get("continuation"))
+                .map(NoteContinuation::parse)
+                .transpose()?,
+            note_tags: objects(notes.and_then(|notes: &Value| notes.get("tags")), NoteTags::parse)?,
+        })
+    }
+
+    /// Loads the profile from the JSON file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content: String = read_file(path)?;
+
+        // Syntax errors are reported with the position in the file
+        parse_json::<Value>(path, &content)?;
+        GameProfile::parse(&content).map_err(|message: String| Error::InvalidProfile(path.to_path_buf(), message))
+    }
+
+    /// Returns the profiles, that are shipped with the tool.
+    pub fn builtin() -> Vec<GameProfile> {
+        BUILTIN_PROFILES
+            .into_iter()
+            .map(|content: &str| GameProfile::parse(content).unwrap())
+            .collect()
+    }
+
+    /// Returns the built-in profile with the id.
+    pub fn builtin_by_id(id: &str) -> Option<GameProfile> {
+        GameProfile::builtin()
+            .into_iter()
+            .find(|profile: &GameProfile| profile.id == id)
+    }
+
+    /// Returns the built-in profile, which fingerprint the game matches.
+    pub fn detect(game: &GameInfo) -> Option<GameProfile> {
+        GameProfile::builtin().into_iter().find(|profile: &GameProfile| {
+            profile
+                .fingerprint
+                .as_ref()
+                .is_some_and(|fingerprint: &Fingerprint| fingerprint.matches(game))
+        })
+    }
+
+    /// Returns whether the parameter of the event command with the code isn't parsed.
+    pub fn skips_parameter(&self, code: Code, parameter: &str) -> bool {
+        self.parameters
+            .iter()
+            .any(|(codes, filter)| (codes.is_empty() || codes.contains(&code)) && filter.skips(parameter))
+    }
+
+    /// Returns whether the variable of the object of the file isn't parsed.
+    pub fn skips_variable(&self, variable_type: &Variable, filename: &str, variable_text: &str) -> bool {
+        self.variables.iter().any(|(variables, filter)| {
+            (variables.is_empty() || variables.contains(variable_type))
+                && filter.applies_to(filename)
+                && filter.skips(variable_text)
+        })
+    }
+
+    /// Returns whether the strings of the plugin are parsed. If the profile lists plugins, only they are parsed.
+    pub fn parses_plugin(&self, name: &str) -> bool {
+        self.plugins
+            .as_ref()
+            .is_none_or(|plugins: &Vec<String>| plugins.iter().any(|plugin: &String| plugin == name))
+    }
+
+    /// Returns whether notes of the files are handled as continuations of descriptions.
+    pub fn has_note_continuation(&self) -> bool {
+        self.note_continuation.is_some()
+    }
+
+    /// Returns the handling of the continuations of descriptions in notes of the file, if notes of the file aren't
+    /// parsed as is.
+    pub fn note_continuation(&self, filename: &str) -> Option<&NoteContinuation> {
+        self.note_continuation
+            .as_ref()
+            .filter(|continuation: &&NoteContinuation| {
+                !continuation
+                    .skip_files
+                    .iter()
+                    .any(|prefix: &String| filename.starts_with(prefix))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game_info(version_id: Option<u64>, plugins: &[&str]) -> GameInfo<'static> {
+        GameInfo {
+            version_id,
+            plugins: plugins.iter().map(|plugin: &&str| plugin.to_string()).collect(),
+            game_path: Path::new(env!("CARGO_MANIFEST_DIR")),
+        }
+    }
+
+    #[test]
+    fn filters_skip_parsed_strings() {
+        let profile: GameProfile = GameProfile::parse(
+            r#"{
+                "id": "test",
+                "parameters": [
+                    { "skip": { "regex": ["^[a-z_]+$"] } },
+                    { "codes": ["system"], "allow": { "starts_with": ["Gab"] } }
+                ],
+                "variables": [
+                    { "variables": ["name"], "files": ["Actors"], "allow": { "equals": ["Levi"] } },
+                    { "skip": { "contains": ["---"] } }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(profile.skips_parameter(Code::Dialogue, "switch_name"));
+        assert!(!profile.skips_parameter(Code::Dialogue, "Hello there."));
+        assert!(profile.skips_parameter(Code::System, "Plugin text"));
+        assert!(!profile.skips_parameter(Code::System, "GabText Hello"));
+
+        assert!(profile.skips_variable(&Variable::Name, "Actors.json", "Unused actor"));
+        assert!(!profile.skips_variable(&Variable::Name, "Actors.json", "Levi"));
+        assert!(!profile.skips_variable(&Variable::Name, "Items.json", "Unused actor"));
+        assert!(profile.skips_variable(&Variable::Description, "Items.json", "--- separator ---"));
+    }
+
+    #[test]
+    fn builtin_profiles_are_valid() {
+        assert!(GameProfile::builtin_by_id("termina").is_some());
+        assert!(GameProfile::builtin_by_id("unknown").is_none());
+    }
+
+    #[test]
+    fn fingerprint_matches_every_condition() {
+        let profile: GameProfile = GameProfile::parse(
+            r#"{
+                "id": "test",
+                "fingerprint": {
+                    "version_ids": [1, 2],
+                    "plugins": ["CorePlugin"],
+                    "files": { "Cargo.toml": null }
+                }
+            }"#,
+        )
+        .unwrap();
+        let fingerprint: &Fingerprint = profile.fingerprint.as_ref().unwrap();
+
+        assert!(fingerprint.matches(&game_info(Some(2), &["CorePlugin", "OtherPlugin"])));
+        assert!(!fingerprint.matches(&game_info(Some(3), &["CorePlugin"])));
+        assert!(!fingerprint.matches(&game_info(None, &["CorePlugin"])));
+        assert!(!fingerprint.matches(&game_info(Some(1), &["OtherPlugin"])));
+    }
+
+    #[test]
+    fn fingerprint_compares_hashes_of_files() {
+        let hash: u64 = xxh3_64(&read(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml")).unwrap());
+        let profile = |hash: u64| -> GameProfile {
+            GameProfile::parse(&format!(
+                r#"{{ "id": "test", "fingerprint": {{ "files": {{ "Cargo.toml": "{hash:x}", "src": null }} }} }}"#
+            ))
+            .unwrap()
+        };
+
+        assert!(profile(hash).fingerprint.unwrap().matches(&game_info(None, &[])));
+        assert!(!profile(hash ^ 1).fingerprint.unwrap().matches(&game_info(None, &[])));
+    }
+
+    #[test]
+    fn builtin_profile_is_detected_by_plugins() {
+        let termina_plugins: [&str; 11] = [
+            "YEP_BattleEngineCore",
+            "YEP_OptionsCore",
+            "SRD_NameInputUpgrade",
+            "YEP_KeyboardConfig",
+            "YEP_ItemCore",
+            "YEP_X_ItemDiscard",
+            "YEP_EquipCore",
+            "YEP_ItemSynthesis",
+            "ARP_CommandIcons",
+            "YEP_X_ItemCategories",
+            "Olivia_OctoBattle",
+        ];
+
+        let detected: Option<GameProfile> = GameProfile::detect(&game_info(Some(1), &termina_plugins));
+        assert_eq!(
+            detected.map(|profile: GameProfile| profile.id).as_deref(),
+            Some("termina")
+        );
+
+        assert!(GameProfile::detect(&game_info(Some(1), &termina_plugins[1..])).is_none());
+    }
+
+    #[test]
+    fn invalid_fingerprint_is_reported() {
+        assert!(GameProfile::parse(r#"{ "id": "test", "fingerprint": {} }"#).is_err());
+        assert!(GameProfile::parse(r#"{ "id": "test", "fingerprint": { "version_ids": ["1"] } }"#).is_err());
+        assert!(GameProfile::parse(r#"{ "id": "test", "fingerprint": { "files": { "a": "xyz" } } }"#).is_err());
+    }
+}
@@ -0,0 +1,702 @@
+use crate::{
+    codes::default_codes,
+    detect_game_profile,
+    error::{create_dir, read_file, read_json, write_file, Error},
+    find_original_path, find_plugins_file_path, game_output_path, metadata_codes, metadata_content,
+    po::{parse_po, to_po_string, PoEntry},
+    profile::GameProfile,
+    read::{read_map, read_other, read_plugins, read_system},
+    rules::Rules,
+    section_names,
+    verify::verify,
+    write::{write_maps, write_other, write_plugins, write_system},
+    Format, ProcessingMode, METADATA_FILE_NAME, SECTION_DIRS,
+};
+use sonic_rs::{JsonValueTrait, Object, Value};
+use std::path::{Path, PathBuf};
+
+/// Part of the game files, that is read and written separately.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Section {
+    Maps,
+    Other,
+    System,
+    Plugins,
+}
+
+impl Section {
+    pub const ALL: [Section; 4] = [Section::Maps, Section::Other, Section::System, Section::Plugins];
+}
+
+/// Options of reading and writing, the same as the options of the command line interface.
+#[derive(Clone)]
+pub struct ProjectOptions {
+    pub romanize: bool,
+    pub disable_custom_processing: bool,
+    pub format: Format,
+    /// Codes of the event commands, which text is extracted
+    pub codes: Vec<u64>,
+    pub processing_mode: ProcessingMode,
+    pub contextual_keys: bool,
+    pub locations: bool,
+    pub split_maps: bool,
+    pub global_dedup: bool,
+    pub speaker_context: bool,
+    pub placeholders: bool,
+    pub shuffle_level: u8,
+    /// Max width of message lines in characters, to which messages are rewrapped when writing
+    pub wrap_width: Option<usize>,
+    pub wrap_lines: usize,
+    /// Directory with original game files, that is used instead of the discovered one
+    pub data_dir: Option<PathBuf>,
+    /// Path to plugins.js file, that is used instead of the discovered one
+    pub plugins_file: Option<PathBuf>,
+    /// Translation directory, that is used instead of `translation` folder of the output directory
+    pub translation_dir: Option<PathBuf>,
+    /// Id of the built-in game profile, that is used instead of the detected one
+    pub game: Option<String>,
+    /// Path to the game profile file, that is used instead of the detected one
+    pub profile: Option<PathBuf>,
+    /// Path to the rules file, that is used instead of `rules.json` of the translation directory
+    pub rules: Option<PathBuf>,
+    /// Whether the format is specified explicitly, so the format of the previous read isn't taken from the metadata
+    pub format_specified: bool,
+    /// Whether the codes are specified explicitly, so the codes of the previous read aren't taken from the metadata
+    pub codes_specified: bool,
+}
+
+impl Default for ProjectOptions {
+    fn default() -> Self {
+        ProjectOptions {
+            romanize: false,
+            disable_custom_processing: false,
+            format: Format::Txt,
+            codes: default_codes(),
+            processing_mode: ProcessingMode::Default,
+            contextual_keys: false,
+            locations: false,
+            split_maps: false,
+            global_dedup: false,
+            speaker_context: false,
+            placeholders: false,
+            shuffle_level: 0,
+            wrap_width: None,
+            wrap_lines: 4,
+            data_dir: None,
+            plugins_file: None,
+            translation_dir: None,
+            game: None,
+            profile: None,
+            rules: None,
+            format_specified: false,
+            codes_specified: false,
+        }
+    }
+}
+
+/// Option, that is taken from the metadata of the previous read, since it must be the same when writing or appending.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MetadataOption {
+    Romanize,
+    DisableCustomProcessing,
+    PoFormat,
+    ContextualKeys,
+    Locations,
+    SplitMaps,
+    SpeakerContext,
+    Placeholders,
+    Codes,
+}
+
+/// Messages, that are logged when files are processed, if logging is enabled.
+#[derive(Clone, Copy, Default)]
+pub struct ProjectMessages {
+    /// Message to log when file is parsed
+    pub file_parsed: &'static str,
+    /// Message to log when file, that is about to be parsed, already exists (default processing mode)
+    pub file_already_parsed: &'static str,
+    /// Message to log when file, that is about to be parsed, doesn't exist (append processing mode)
+    pub file_is_not_parsed: &'static str,
+    /// Message to log when file is written
+    pub file_written: &'static str,
+    /// Message to log when file is verified
+    pub file_verified: &'static str,
+    /// Message to log when translation isn't written, since its placeholders don't match
+    pub placeholders_mismatch: &'static str,
+}
+
+/// Translation file of a single section, e.g. `maps/maps.txt` or `other/items.po`.
+pub struct TranslationFile {
+    /// Folder of the translation directory, that contains the file: maps, other or plugins
+    pub dir: String,
+    /// Name of the section, e.g. `maps` or `items`
+    pub name: String,
+    /// Entries of the file. Entries of .txt files are parsed from the lines of the file, and their line is the number
+    /// of the line.
+    pub entries: Vec<PoEntry>,
+}
+
+/// In-memory model of all translation files of the translation directory.
+pub struct TranslationSet {
+    pub format: Format,
+    pub files: Vec<TranslationFile>,
+}
+
+impl TranslationSet {
+    /// Loads translation files of maps, other and plugins folders of the translation directory.
+    /// # Parameters
+    /// * `translation_path` - path to the translation directory
+    /// * `format` - format of translation files
+    pub fn load(translation_path: &Path, format: Format) -> Result<Self, Error> {
+        let mut files: Vec<TranslationFile> = Vec::new();
+
+        for dir in SECTION_DIRS {
+            let path: &Path = &translation_path.join(dir);
+
+            if !path.exists() {
+                continue;
+            }
+
+            for name in section_names(path, format)? {
+                let entries: Vec<PoEntry> = match format {
+                    Format::Txt => {
+                        let original_path: &Path = &path.join(format!("{name}.txt"));
+                        let translated_path: &Path = &path.join(format!("{name}_trans.txt"));
+                        let original_content: String = read_file(original_path)?;
+                        let translated_content: String = read_file(translated_path)?;
+                        let original_lines: Vec<&str> = original_content.split('\n').collect();
+                        let translated_lines: Vec<&str> = translated_content.split('\n').collect();
+
+                        if original_lines.len() != translated_lines.len() {
+                            return Err(Error::LinesCountMismatch {
+                                path: translated_path.to_path_buf(),
+                                original: original_lines.len(),
+                                translated: translated_lines.len(),
+                            });
+                        }
+
+                        original_lines
+                            .into_iter()
+                            .zip(translated_lines)
+                            .enumerate()
+                            .map(|(i, (original, translated))| {
+                                let mut entry: PoEntry =
+                                    PoEntry::from_key(original.to_string(), translated.to_string(), Vec::new());
+
+                                entry.line = i + 1;
+                                entry
+                            })
+                            .collect()
+                    }
+                    Format::Po => {
+                        let po_path: &Path = &path.join(format!("{name}.po"));
+                        parse_po(&read_file(po_path)?)
+                    }
+                };
+
+                files.push(TranslationFile {
+                    dir: dir.to_string(),
+                    name,
+                    entries,
+                });
+            }
+        }
+
+        Ok(TranslationSet { format, files })
+    }
+
+    /// Saves translation of all files back to the translation directory. Original text of .txt files isn't changed.
+    /// # Parameters
+    /// * `translation_path` - path to the translation directory
+    pub fn save(&self, translation_path: &Path) -> Result<(), Error> {
+        for file in &self.files {
+            let path: &Path = &translation_path.join(&file.dir);
+
+            let (file_path, content): (PathBuf, String) = match self.format {
+                Format::Txt => (
+                    path.join(format!("{}_trans.txt", file.name)),
+                    file.entries
+                        .iter()
+                        .map(|entry: &PoEntry| entry.translated.as_str())
+                        .collect::<Vec<&str>>()
+                        .join("\n"),
+                ),
+                Format::Po => (path.join(format!("{}.po", file.name)), to_po_string(&file.entries)),
+            };
+
+            write_file(&file_path, content)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the translation file of the section in the specified folder.
+    pub fn file(&self, dir: &str, name: &str) -> Option<&TranslationFile> {
+        self.files
+            .iter()
+            .find(|file: &&TranslationFile| file.dir == dir && file.name == name)
+    }
+
+    /// Returns the mutable translation file of the section in the specified folder.
+    pub fn file_mut(&mut self, dir: &str, name: &str) -> Option<&mut TranslationFile> {
+        self.files
+            .iter_mut()
+            .find(|file: &&mut TranslationFile| file.dir == dir && file.name == name)
+    }
+}
+
+/// Game, which text is read to the translation directory and written back with the translation.
+pub struct Project {
+    original_path: PathBuf,
+    plugins_file_path: PathBuf,
+    translation_path: PathBuf,
+    output_path: PathBuf,
+    game_profile: Option<GameProfile>,
+    rules: Rules,
+    metadata_applied: bool,
+    pub options: ProjectOptions,
+    /// Messages, that are logged when files are processed. Nothing is logged, if they aren't set
+    pub messages: Option<ProjectMessages>,
+}
+
+impl Project {
+    /// Creates a project of the game, which files are located in `original` or `data` folder of the input directory, or
+    /// of its `www` folder. Translation is read to `translation` folder of the output directory, and written to its
+    /// `output` folder. Directories and files, specified in the options, are used instead of the discovered ones.
+    /// # Parameters
+    /// * `input_dir` - directory of the game
+    /// * `output_dir` - directory, that contains translation and output folders
+    /// * `options` - options of reading and writing
+    pub fn new(input_dir: &Path, output_dir: &Path, options: ProjectOptions) -> Result<Self, Error> {
+        for dir in [input_dir, output_dir] {
+            if !dir.exists() {
+                return Err(Error::MissingDirectory(dir.to_path_buf()));
+            }
+        }
+
+        let original_path: PathBuf = match &options.data_dir {
+            Some(data_dir) if data_dir.exists() => data_dir.clone(),
+            Some(data_dir) => return Err(Error::MissingDirectory(data_dir.clone())),
+            None => find_original_path(input_dir).ok_or_else(|| Error::MissingDirectory(input_dir.join("data")))?,
+        };
+        let translation_path: PathBuf = options
+            .translation_dir
+            .clone()
+            .unwrap_or_else(|| output_dir.join("translation"));
+
+        let plugins_file_path: PathBuf = match &options.plugins_file {
+            Some(plugins_file) if plugins_file.exists() => plugins_file.clone(),
+            Some(plugins_file) => return Err(Error::MissingFile(plugins_file.clone())),
+            None => find_plugins_file_path(input_dir, &original_path, &translation_path.join("plugins")),
+        };
+
+        let game_profile: Option<GameProfile> = if options.disable_custom_processing {
+            None
+        } else if let Some(profile_file_path) = &options.profile {
+            Some(GameProfile::load(profile_file_path)?)
+        } else if let Some(game) = &options.game {
+            Some(GameProfile::builtin_by_id(game).ok_or_else(|| Error::UnknownGame(game.clone()))?)
+        } else {
+            detect_game_profile(
+                &original_path.join("System.json"),
+                &plugins_file_path,
+                original_path.parent().unwrap_or(input_dir),
+            )
+        };
+
+        let rules: Rules = Rules::find(options.rules.as_deref(), &translation_path)?;
+
+        Ok(Project {
+            plugins_file_path,
+            original_path,
+            translation_path,
+            output_path: game_output_path(input_dir, &output_dir.join("output")),
+            game_profile,
+            rules,
+            metadata_applied: false,
+            options,
+            messages: None,
+        })
+    }
+
+    pub fn original_path(&self) -> &Path {
+        &self.original_path
+    }
+
+    pub fn plugins_file_path(&self) -> &Path {
+        &self.plugins_file_path
+    }
+
+    pub fn translation_path(&self) -> &Path {
+        &self.translation_path
+    }
+
+    pub fn output_path(&self) -> &Path {
+        &self.output_path
+    }
+
+    pub fn game_profile(&self) -> &Option<GameProfile> {
+        &self.game_profile
+    }
+
+    fn logging(&self) -> (bool, ProjectMessages) {
+        (self.messages.is_some(), self.messages.unwrap_or_default())
+    }
+
+    /// Applies the options of the previous read from the metadata file of the translation directory, once per project.
+    /// Writing uses romanization, custom processing, format, codes and placeholders of the previous read, while reading
+    /// in append mode uses all options, that change the layout of translation files, except romanization. Format and
+    /// codes, that are specified explicitly, are kept. Returns the options, that were changed.
+    /// # Parameters
+    /// * `writing` - whether the options are applied for writing, or for reading
+    pub fn apply_metadata(&mut self, writing: bool) -> Result<Vec<MetadataOption>, Error> {
+        let metadata_file_path: &Path = &self.translation_path.join(METADATA_FILE_NAME);
+        let mut changed: Vec<MetadataOption> = Vec::new();
+
+        if self.metadata_applied
+            || !metadata_file_path.exists()
+            || (!writing && self.options.processing_mode != ProcessingMode::Append)
+        {
+            self.metadata_applied = true;
+            return Ok(changed);
+        }
+
+        let metadata: Object = read_json(metadata_file_path)?;
+        let flag = |key: &str| metadata.get(&key).and_then(|value: &Value| value.as_bool()) == Some(true);
+        let options: &mut ProjectOptions = &mut self.options;
+
+        if writing && flag("romanize") && !options.romanize {
+            options.romanize = true;
+            changed.push(MetadataOption::Romanize);
+        }
+
+        if writing && flag("disableCustomProcessing") && self.game_profile.is_some() {
+            options.disable_custom_processing = true;
+            self.game_profile = None;
+            changed.push(MetadataOption::DisableCustomProcessing);
+        }
+
+        if metadata.get(&"format").and_then(|value: &Value| value.as_str()) == Some("po")
+            && !options.format_specified
+            && options.format != Format::Po
+        {
+            options.format = Format::Po;
+            changed.push(MetadataOption::PoFormat);
+        }
+
+        if !writing {
+            for (key, option, value) in [
+                (
+                    "contextualKeys",
+                    MetadataOption::ContextualKeys,
+                    &mut options.contextual_keys,
+                ),
+                ("locations", MetadataOption::Locations, &mut options.locations),
+                ("splitMaps", MetadataOption::SplitMaps, &mut options.split_maps),
+                (
+                    "speakerContext",
+                    MetadataOption::SpeakerContext,
+                    &mut options.speaker_context,
+                ),
+            ] {
+                if flag(key) && !*value {
+                    *value = true;
+                    changed.push(option);
+                }
+            }
+        }
+
+        if flag("placeholders") && !options.placeholders {
+            options.placeholders = true;
+            changed.push(MetadataOption::Placeholders);
+        }
+
+        if let Some(codes) = metadata_codes(&metadata) {
+            if !options.codes_specified {
+                options.codes = codes;
+                changed.push(MetadataOption::Codes);
+            }
+        }
+
+        self.metadata_applied = true;
+        Ok(changed)
+    }
+
+    /// Reads text of the specified sections of the game to the translation directory, and writes the metadata of the
+    /// read, so the translation is written with the same options. In append mode, the options of the previous read are
+    /// applied first. Files, that can't be read, don't stop reading of the rest of the files, and errors of all of them
+    /// are returned.
+    pub fn read(&mut self, sections: &[Section]) -> Result<(), Vec<Error>> {
+        self.apply_metadata(false).map_err(|error: Error| vec![error])?;
+
+        let options: &ProjectOptions = &self.options;
+        let (logging, messages): (bool, ProjectMessages) = self.logging();
+        let maps_path: &Path = &self.translation_path.join("maps");
+        let other_path: &Path = &self.translation_path.join("other");
+        let plugins_path: &Path = &self.translation_path.join("plugins");
+
+        for path in [maps_path, other_path] {
+            create_dir(path).map_err(|error: Error| vec![error])?;
+        }
+
+        write_file(
+            &self.translation_path.join(METADATA_FILE_NAME),
+            metadata_content(
+                options.romanize,
+                options.disable_custom_processing,
+                options.format,
+                options.contextual_keys,
+                options.locations,
+                options.split_maps,
+                options.speaker_context,
+                &options.codes,
+                options.placeholders,
+            ),
+        )
+        .map_err(|error: Error| vec![error])?;
+
+        let mut errors: Vec<Error> = Vec::new();
+
+        if sections.contains(&Section::Maps) {
+            errors.extend(read_map(
+                &self.original_path,
+                maps_path,
+                options.romanize,
+                logging,
+                messages.file_parsed,
+                messages.file_already_parsed,
+                messages.file_is_not_parsed,
+                &self.game_profile,
+                &self.rules,
+                &options.processing_mode,
+                options.format,
+                options.contextual_keys,
+                options.locations,
+                options.split_maps,
+                options.global_dedup,
+                options.speaker_context,
+                &options.codes,
+                options.placeholders,
+            ));
+        }
+
+        if sections.contains(&Section::Other) {
+            errors.extend(read_other(
+                &self.original_path,
+                other_path,
+                options.romanize,
+                logging,
+                messages.file_parsed,
+                messages.file_already_parsed,
+                messages.file_is_not_parsed,
+                &self.game_profile,
+                &self.rules,
+                &options.processing_mode,
+                options.format,
+                options.contextual_keys,
+                options.locations,
+                options.speaker_context,
+                &options.codes,
+                options.placeholders,
+            ));
+        }
+
+        if sections.contains(&Section::System) {
+            errors.extend(
+                read_system(
+                    &self.original_path.join("System.json"),
+                    other_path,
+                    options.romanize,
+                    logging,
+                    messages.file_parsed,
+                    messages.file_already_parsed,
+                    messages.file_is_not_parsed,
+                    &self.rules,
+                    &options.processing_mode,
+                    options.format,
+                    options.contextual_keys,
+                    options.locations,
+                )
+                .err(),
+            );
+        }
+
+        if sections.contains(&Section::Plugins) && self.plugins_file_path.exists() {
+            create_dir(plugins_path).map_err(|error: Error| vec![error])?;
+
+            errors.extend(
+                read_plugins(
+                    &self.plugins_file_path,
+                    plugins_path,
+                    options.romanize,
+                    logging,
+                    messages.file_parsed,
+                    messages.file_already_parsed,
+                    messages.file_is_not_parsed,
+                    &self.game_profile,
+                    &self.rules,
+                    &options.processing_mode,
+                    options.format,
+                    options.contextual_keys,
+                    options.locations,
+                )
+                .err(),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Writes translation of the specified sections from the translation directory to the output directory, with the
+    /// options of the previous read applied first. Files, that can't be written, don't stop writing of the rest of the
+    /// files, and errors of all of them are returned.
+    pub fn write(&mut self, sections: &[Section]) -> Result<(), Vec<Error>> {
+        self.apply_metadata(true).map_err(|error: Error| vec![error])?;
+
+        let options: &ProjectOptions = &self.options;
+        let (logging, messages): (bool, ProjectMessages) = self.logging();
+        let maps_path: &Path = &self.translation_path.join("maps");
+        let other_path: &Path = &self.translation_path.join("other");
+        let plugins_path: &Path = &self.translation_path.join("plugins");
+        let data_output_path: &Path = &self.output_path.join("data");
+        let plugins_output_path: &Path = &self.output_path.join("js");
+
+        for path in [maps_path, other_path] {
+            if !path.exists() {
+                return Err(vec![Error::MissingDirectory(path.to_path_buf())]);
+            }
+        }
+
+        for path in [data_output_path, plugins_output_path] {
+            create_dir(path).map_err(|error: Error| vec![error])?;
+        }
+
+        let mut errors: Vec<Error> = Vec::new();
+
+        if sections.contains(&Section::Maps) {
+            errors.extend(write_maps(
+                maps_path,
+                &self.original_path,
+                data_output_path,
+                options.romanize,
+                options.shuffle_level,
+                logging,
+                messages.file_written,
+                &self.game_profile,
+                &self.rules,
+                options.format,
+                &options.codes,
+                options.placeholders,
+                messages.placeholders_mismatch,
+                options.wrap_width,
+                options.wrap_lines,
+            ));
+        }
+
+        if sections.contains(&Section::Other) {
+            errors.extend(write_other(
+                other_path,
+                &self.original_path,
+                data_output_path,
+                options.romanize,
+                options.shuffle_level,
+                logging,
+                messages.file_written,
+                &self.game_profile,
+                &self.rules,
+                options.format,
+                &options.codes,
+                options.placeholders,
+                messages.placeholders_mismatch,
+                options.wrap_width,
+                options.wrap_lines,
+            ));
+        }
+
+        if sections.contains(&Section::System) {
+            errors.extend(
+                write_system(
+                    &self.original_path.join("System.json"),
+                    other_path,
+                    data_output_path,
+                    options.romanize,
+                    options.shuffle_level,
+                    logging,
+                    messages.file_written,
+                    &self.rules,
+                    options.format,
+                )
+                .err(),
+            );
+        }
+
+        let plugins_translation_file_name: &str = match options.format {
+            Format::Txt => "plugins_trans.txt",
+            Format::Po => "plugins.po",
+        };
+
+        if sections.contains(&Section::Plugins)
+            && self.plugins_file_path.exists()
+            && plugins_path.join(plugins_translation_file_name).exists()
+        {
+            errors.extend(
+                write_plugins(
+                    &self.plugins_file_path,
+                    plugins_path,
+                    plugins_output_path,
+                    options.romanize,
+                    options.shuffle_level,
+                    logging,
+                    messages.file_written,
+                    &self.game_profile,
+                    &self.rules,
+                    options.format,
+                )
+                .err(),
+            );
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Verifies that reading and writing of the specified sections of the game is lossless, with the options of the
+    /// previous read applied first. Returns the number of fields of written files, that differ from the original files,
+    /// along with errors of the files, that couldn't be read or written.
+    pub fn verify(&mut self, sections: &[Section]) -> (usize, Vec<Error>) {
+        if let Err(error) = self.apply_metadata(true) {
+            return (0, vec![error]);
+        }
+
+        let (logging, messages): (bool, ProjectMessages) = self.logging();
+
+        verify(
+            &self.original_path,
+            Some(self.plugins_file_path.as_path()).filter(|path: &&Path| path.exists()),
+            self.options.romanize,
+            &self.game_profile,
+            &self.rules,
+            &self.options.codes,
+            (
+                !sections.contains(&Section::Maps),
+                !sections.contains(&Section::Other),
+                !sections.contains(&Section::System),
+                !sections.contains(&Section::Plugins),
+            ),
+            logging,
+            messages.file_verified,
+        )
+    }
+
+    /// Loads the translation files of the project into memory.
+    pub fn translation(&self) -> Result<TranslationSet, Error> {
+        TranslationSet::load(&self.translation_path, self.options.format)
+    }
+}
@@ -1,346 +1,585 @@
 #![allow(clippy::too_many_arguments)]
-use crate::{romanize_string, Code, GameType, ProcessingMode, Variable, STRING_IS_ONLY_SYMBOLS_RE};
-use indexmap::{IndexMap, IndexSet};
+use crate::{
+    codes::{get_code_rule, join_sequence_lines, parsed_codes, script_string_literals, Extraction},
+    database_fields, decode_plugin_parameter,
+    error::{invalid_value, parse_json, read_file, read_json, write_file, Error},
+    find_map_sections, is_map_file, is_other_file,
+    placeholders::protect_codes,
+    po::{parse_po, to_po_string, PoEntry},
+    profile::GameProfile,
+    project::Section,
+    read_game_files, romanize_string,
+    rules::Rules,
+    strip_plugins_prefix, Code, DatabaseFields, Format, ProcessingMode, Variable, CONTEXT_SEPARATOR,
+    PLUGIN_PARAMETER_IS_NOT_TEXT_RE, STRING_IS_ONLY_SYMBOLS_RE,
+};
+use indexmap::IndexMap;
 use rayon::prelude::*;
-use sonic_rs::{from_str, Array, JsonContainerTrait, JsonValueTrait, Object, Value};
+use sonic_rs::{to_string, Array, JsonContainerTrait, JsonValueTrait, Object, Value};
 use std::{
-    ffi::OsString,
-    fs::{read_dir, read_to_string, write, DirEntry},
+    collections::{HashMap, HashSet},
+    fs::remove_file,
     hash::{BuildHasher, BuildHasherDefault},
     path::Path,
-    str::from_utf8_unchecked,
 };
 use xxhash_rust::xxh3::Xxh3;
 
-trait Join {
-    fn join(&self, delimiter: &str) -> String;
-}
-
-impl<T: ToString + AsRef<str>, S: std::hash::BuildHasher> Join for IndexSet<T, S> {
-    fn join(&self, delimiter: &str) -> String {
-        let mut joined: String = String::new();
-        joined.push_str(self.get_index(0).unwrap().as_ref());
-
-        for item in self.iter().skip(1) {
-            joined.push_str(delimiter);
-            joined.push_str(item.as_ref());
-        }
+fn parse_parameter(
+    code: Code,
+    parameter: &str,
+    section: Section,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+) -> Option<String> {
+    if STRING_IS_ONLY_SYMBOLS_RE.is_match(parameter) || rules.skips_parameter(section, code, parameter) {
+        return None;
+    }
 
-        joined
+    if game_profile
+        .as_ref()
+        .is_some_and(|game_profile: &GameProfile| game_profile.skips_parameter(code, parameter))
+    {
+        return None;
     }
+
+    Some(parameter.to_string())
 }
 
-#[allow(clippy::single_match, clippy::match_single_binding, unused_mut)]
-fn parse_parameter(code: Code, mut parameter: &str, game_type: &Option<GameType>) -> Option<String> {
-    if STRING_IS_ONLY_SYMBOLS_RE.is_match(parameter) {
+/// Parses the field of the object of the database file, without handling notes as continuations of descriptions.
+fn parse_field(
+    variable_text: &str,
+    variable_type: &Variable,
+    filename: &str,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+) -> Option<String> {
+    if STRING_IS_ONLY_SYMBOLS_RE.is_match(variable_text) || rules.skips_variable(variable_type, variable_text) {
         return None;
     }
 
-    if let Some(game_type) = game_type {
-        match game_type {
-            GameType::Termina => {
-                if parameter
-                    .chars()
-                    .all(|char: char| char.is_ascii_lowercase() || char.is_ascii_punctuation())
-                {
-                    return None;
-                }
-
-                match code {
-                    Code::System => {
-                        if !parameter.starts_with("Gab")
-                            && (!parameter.starts_with("choice_text") || parameter.ends_with("????"))
-                        {
-                            return None;
-                        }
-                    }
-                    _ => {}
-                }
-            } // custom processing for other games
-        }
+    if game_profile
+        .as_ref()
+        .is_some_and(|game_profile: &GameProfile| game_profile.skips_variable(variable_type, filename, variable_text))
+    {
+        return None;
     }
 
-    Some(parameter.to_string())
+    Some(variable_text.to_string())
 }
 
-#[allow(clippy::single_match, clippy::match_single_binding, unused_mut)]
 fn parse_variable(
-    mut variable_text: String,
+    variable_text: &str,
     variable_type: &Variable,
     filename: &str,
-    game_type: &Option<GameType>,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
 ) -> Option<(String, bool)> {
-    if STRING_IS_ONLY_SYMBOLS_RE.is_match(&variable_text) {
-        return None;
+    let mut variable_text: String = parse_field(variable_text, variable_type, filename, game_profile, rules)?;
+    let mut is_continuation_of_description: bool = false;
+
+    if let Some(game_profile) = game_profile {
+        if *variable_type == Variable::Note {
+            if let Some(note_continuation) = game_profile.note_continuation(filename) {
+                // The rest of the note isn't displayed in-game
+                variable_text = r"\#".to_string() + note_continuation.description_continuation(&variable_text)?;
+                is_continuation_of_description = true;
+            }
+        }
     }
 
-    let mut is_continuation_of_description: bool = false;
+    Some((variable_text, is_continuation_of_description))
+}
 
-    if let Some(game_type) = game_type {
-        match game_type {
-            GameType::Termina => {
-                if variable_text.contains("---") || variable_text.starts_with("///") {
-                    return None;
-                }
+/// Returns the name of the file, that holds translation of the section, for the file existence checks and logging.
+fn translation_file_name(name: &str, format: Format) -> String {
+    match format {
+        Format::Txt => format!("{name}_trans.txt"),
+        Format::Po => format!("{name}.po"),
+    }
+}
 
-                match variable_type {
-                    Variable::Name | Variable::Nickname => {
-                        if filename.starts_with("Ac") {
-                            if ![
-                                "Levi",
-                                "Marina",
-                                "Daan",
-                                "Abella",
-                                "O'saa",
-                                "Blood golem",
-                                "Marcoh",
-                                "Karin",
-                                "Olivia",
-                                "Ghoul",
-                                "Villager",
-                                "August",
-                                "Caligura",
-                                "Henryk",
-                                "Pav",
-                                "Tanaka",
-                                "Samarie",
-                            ]
-                            .contains(&variable_text.as_str())
-                            {
-                                return None;
-                            }
-                        } else if filename.starts_with("Ar") {
-                            if variable_text.starts_with("test_armor") {
-                                return None;
-                            }
-                        } else if filename.starts_with("Cl") {
-                            if [
-                                "Girl",
-                                "Kid demon",
-                                "Captain",
-                                "Marriage",
-                                "Marriage2",
-                                "Baby demon",
-                                "Buckman",
-                                "Nas'hrah",
-                                "Skeleton",
-                            ]
-                            .contains(&variable_text.as_str())
-                            {
-                                return None;
-                            }
-                        } else if filename.starts_with("En") {
-                            if ["Spank Tank", "giant", "test"].contains(&variable_text.as_str()) {
-                                return None;
-                            }
-                        } else if filename.starts_with("It") {
-                            if [
-                                "Torch",
-                                "Flashlight",
-                                "Stick",
-                                "Quill",
-                                "Empty scroll",
-                                "Soul stone_NOT_USE",
-                                "Cube of depths",
-                                "Worm juice",
-                                "Silver shilling",
-                                "Coded letter #1 - UNUSED",
-                                "Black vial",
-                                "Torturer's notes 1",
-                                "Purple vial",
-                                "Orange vial",
-                                "Red vial",
-                                "Green vial",
-                                "Pinecone pig instructions",
-                                "Grilled salmonsnake meat",
-                                "Empty scroll",
-                                "Water vial",
-                                "Blood vial",
-                                "Devil's Grass",
-                                "Stone",
-                                "Codex #1",
-                                "The Tale of the Pocketcat I",
-                                "The Tale of the Pocketcat II",
-                            ]
-                            .contains(&variable_text.as_str())
-                                || variable_text.starts_with("The Fellowship")
-                                || variable_text.starts_with("Studies of")
-                                || variable_text.starts_with("Blueish")
-                                || variable_text.starts_with("Skeletal")
-                                || variable_text.ends_with("soul")
-                                || variable_text.ends_with("schematics")
-                            {
-                                return None;
-                            }
-                        } else if filename.starts_with("We") && variable_text == "makeshift2" {
-                            return None;
-                        }
-                    }
-                    Variable::Message1 | Variable::Message2 | Variable::Message3 | Variable::Message4 => {
-                        return None;
-                    }
-                    Variable::Note => {
-                        if filename.starts_with("Ac") {
-                            return None;
-                        }
+/// Reads already existing translation of the section for append processing mode. Returns None if it doesn't exist,
+/// and an error, if it can't be read, since appending to it would lose the translation.
+fn read_translation_map(
+    output_path: &Path,
+    name: &str,
+    format: Format,
+) -> Result<Option<IndexMap<String, String, BuildHasherDefault<Xxh3>>>, Error> {
+    let translation_file_path: &Path = &output_path.join(translation_file_name(name, format));
 
-                        if !filename.starts_with("Cl") {
-                            let mut variable_text_chars: std::str::Chars = variable_text.chars();
-
-                            if !variable_text.starts_with("flesh puppetry") {
-                                if let Some(first_char) = variable_text_chars.next() {
-                                    if let Some(second_char) = variable_text_chars.next() {
-                                        if ((first_char == '\n' && second_char != '\n')
-                                            || (first_char.is_ascii_alphabetic()
-                                                || first_char == '"'
-                                                || variable_text.starts_with("4 sticks")))
-                                            && !['.', '!', '/', '?'].contains(&first_char)
-                                        {
-                                            is_continuation_of_description = true;
-                                        }
-                                    }
-                                }
-                            }
+    if !translation_file_path.exists() {
+        return Ok(None);
+    }
 
-                            if is_continuation_of_description {
-                                if let Some((mut left, _)) = variable_text.trim_start().split_once('\n') {
-                                    left = left.trim();
+    let mut translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
 
-                                    if !left.ends_with(['.', '%', '!', '"']) {
-                                        return None;
-                                    }
+    match format {
+        Format::Txt => {
+            let original_file_path: &Path = &output_path.join(format!("{name}.txt"));
 
-                                    variable_text = r"\#".to_string() + left;
-                                } else {
-                                    if !variable_text.ends_with(['.', '%', '!', '"']) {
-                                        return None;
-                                    }
+            if !original_file_path.exists() {
+                return Err(Error::MissingTranslationFile(original_file_path.to_path_buf()));
+            }
 
-                                    variable_text = r"\#".to_string() + &variable_text
-                                }
-                            } else {
-                                return None;
-                            }
+            let original_content: String = read_file(original_file_path)?;
+            let translated_content: String = read_file(translation_file_path)?;
+            let original_lines: Vec<&str> = original_content.par_split('\n').collect();
+            let translated_lines: Vec<&str> = translated_content.par_split('\n').collect();
+
+            if original_lines.len() != translated_lines.len() {
+                return Err(Error::LinesCountMismatch {
+                    path: translation_file_path.to_path_buf(),
+                    original: original_lines.len(),
+                    translated: translated_lines.len(),
+                });
+            }
+
+            for (original, translated) in original_lines.into_iter().zip(translated_lines) {
+                translation_map.insert(original.to_string(), translated.to_string());
+            }
+        }
+        Format::Po => {
+            for entry in parse_po(&read_file(translation_file_path)?) {
+                translation_map.insert(entry.key(), entry.translated);
+            }
+        }
+    }
+
+    Ok(Some(translation_map))
+}
+
+/// Inserts parsed field of the object of the database file with the location it occurs at. Lines of the field are
+/// joined with `\#`.
+fn insert_field<T: BuildHasher>(
+    mut field: String,
+    location: String,
+    romanize: bool,
+    processing_mode: &ProcessingMode,
+    lines: &mut IndexMap<String, Vec<String>, T>,
+    map: &mut IndexMap<String, String, T>,
+) {
+    if romanize {
+        field = romanize_string(field);
+    }
+
+    let joined: String = field
+        .split('\n')
+        .map(|line: &str| line.trim())
+        .collect::<Vec<_>>()
+        .join(r"\#");
+
+    insert_line(joined, location, processing_mode, lines, map);
+}
+
+/// Inserts parsed line with the location it occurs at. In append processing mode, line is also inserted to translation
+/// map with empty translation at the same position, if it's not translated yet.
+fn insert_line<T: BuildHasher>(
+    line: String,
+    location: String,
+    processing_mode: &ProcessingMode,
+    lines: &mut IndexMap<String, Vec<String>, T>,
+    map: &mut IndexMap<String, String, T>,
+) {
+    if processing_mode == ProcessingMode::Append && !map.contains_key(&line) {
+        map.shift_insert(lines.len(), line.clone(), "".into());
+    }
+
+    lines.entry(line).or_default().push(location);
+}
+
+/// Adds an entry, keyed by the location and the text, after every line, that occurs in several locations. Translation of
+/// such entry overrides the translation of the line only in its location, and if it's left empty, the translation of
+/// the line is used. In append processing mode, missing entries are also inserted to translation map after their line.
+fn add_contextual_keys(
+    processing_mode: &ProcessingMode,
+    lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>>,
+    translation_map: &mut IndexMap<String, String, BuildHasherDefault<Xxh3>>,
+) -> IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> {
+    let mut contextual_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+    for (line, locations) in lines {
+        let contextual_keys: Vec<(String, String)> = if locations.len() > 1 {
+            locations
+                .iter()
+                .map(|location: &String| (format!("{location}{CONTEXT_SEPARATOR}{line}"), location.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        if processing_mode == ProcessingMode::Append {
+            if let Some(mut index) = translation_map.get_index_of(&line) {
+                for (key, _) in &contextual_keys {
+                    match translation_map.get_index_of(key) {
+                        Some(key_index) => index = key_index,
+                        None => {
+                            index += 1;
+                            translation_map.shift_insert(index, key.clone(), "".into());
                         }
                     }
-                    _ => {}
                 }
-            } // custom processing for other games
+            }
+        }
+
+        contextual_lines.insert(line, locations);
+
+        for (key, location) in contextual_keys {
+            contextual_lines.entry(key).or_default().push(location);
         }
     }
 
-    Some((variable_text, is_continuation_of_description))
+    contextual_lines
 }
 
-fn parse_list<T: BuildHasher>(
+/// Writes locations of every parsed line of the section to `{name}.locations.json` file in output_path. The file is
+/// only meant to be inspected by translators, and it's not used when writing.
+fn write_locations(
+    output_path: &Path,
+    name: &str,
+    lines: &IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>>,
+) -> Result<(), Error> {
+    let mut content: String = String::from("{\n");
+
+    for (i, (line, locations)) in lines.iter().enumerate() {
+        if i > 0 {
+            content.push_str(",\n");
+        }
+
+        content.push_str(&format!(
+            "    {}: {}",
+            to_string(line).unwrap(),
+            to_string(locations).unwrap()
+        ));
+    }
+
+    content.push_str("\n}\n");
+    write_file(&output_path.join(format!("{name}.locations.json")), content)
+}
+
+/// Writes parsed lines of the section to output_path in the specified format.
+fn write_lines(
+    output_path: &Path,
+    name: &str,
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+    processing_mode: &ProcessingMode,
+    mut lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>>,
+    mut translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>>,
+    mut comments: Option<IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>>>,
+) -> Result<(), Error> {
+    if contextual_keys {
+        lines = add_contextual_keys(processing_mode, lines, &mut translation_map);
+    }
+
+    if locations {
+        write_locations(output_path, name, &lines)?;
+    }
+
+    match format {
+        Format::Txt => {
+            let (original_content, translated_content) = if processing_mode == ProcessingMode::Append {
+                let collected: (Vec<String>, Vec<String>) = translation_map.into_iter().unzip();
+                (collected.0.join("\n"), collected.1.join("\n"))
+            } else {
+                (
+                    lines.keys().map(String::as_str).collect::<Vec<_>>().join("\n"),
+                    "\n".repeat(lines.len().saturating_sub(1)),
+                )
+            };
+
+            write_file(&output_path.join(format!("{name}.txt")), original_content)?;
+            write_file(&output_path.join(format!("{name}_trans.txt")), translated_content)
+        }
+        Format::Po => {
+            let mut entries: Vec<PoEntry> = if processing_mode == ProcessingMode::Append {
+                // Translation, that needs review, stays fuzzy after appending
+                let po_file_path: &Path = &output_path.join(format!("{name}.po"));
+                let fuzzy_keys: HashSet<String, BuildHasherDefault<Xxh3>> = if po_file_path.exists() {
+                    parse_po(&read_file(po_file_path)?)
+                        .into_iter()
+                        .filter(|entry: &PoEntry| entry.fuzzy)
+                        .map(|entry: PoEntry| entry.key())
+                        .collect()
+                } else {
+                    HashSet::default()
+                };
+
+                translation_map
+                    .into_iter()
+                    .map(|(key, translated)| {
+                        let references: Vec<String> = lines.swap_remove(&key).unwrap_or_default();
+                        let fuzzy: bool = fuzzy_keys.contains(&key);
+                        let mut entry: PoEntry = PoEntry::from_key(key, translated, references);
+                        entry.fuzzy = fuzzy;
+                        entry
+                    })
+                    .collect()
+            } else {
+                lines
+                    .into_iter()
+                    .map(|(key, references)| PoEntry::from_key(key, String::new(), references))
+                    .collect()
+            };
+
+            if let Some(comments) = comments.as_mut() {
+                for entry in entries
+                    .iter_mut()
+                    .filter(|entry: &&mut PoEntry| entry.context.is_none())
+                {
+                    entry.comments = comments.swap_remove(&entry.original).unwrap_or_default();
+                }
+            }
+
+            write_file(&output_path.join(format!("{name}.po")), to_po_string(&entries))
+        }
+    }
+}
+
+/// Parses text of the event commands of the list, which codes are allowed, into lines of the section. Speaker names
+/// are parsed to the separate speakers section.
+pub fn parse_list<T: BuildHasher>(
     list: &Array,
     allowed_codes: &[u64],
+    location: &str,
     romanize: bool,
-    game_type: &Option<GameType>,
+    placeholders: bool,
+    section: Section,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
     processing_mode: &ProcessingMode,
-    set: &mut IndexSet<String, T>,
+    lines: &mut IndexMap<String, Vec<String>, T>,
     map: &mut IndexMap<String, String, T>,
+    speakers_processing_mode: &ProcessingMode,
+    speakers_lines: &mut IndexMap<String, Vec<String>, T>,
+    speakers_map: &mut IndexMap<String, String, T>,
+    mut comments: Option<&mut IndexMap<String, Vec<String>, T>>,
 ) {
-    let mut in_sequence: bool = false;
+    // Code of the commands, that continue current sequence of lines, and the kind of the sequence
+    let mut sequence: Option<(u64, Code)> = None;
     let mut line: Vec<String> = Vec::with_capacity(256);
+    let mut line_start: usize = 0;
+    // Speaker of the Show Text header, which applies only to the dialogue lines directly after it
+    let mut speaker: Option<String> = None;
+    let mut line_speaker: Option<String> = None;
 
-    for item in list {
-        let code: u64 = item["code"].as_u64().unwrap();
+    for (it, item) in list.iter().enumerate() {
+        let header_speaker: Option<String> = speaker.take();
+        // Commands without a code end the current sequence and are skipped
+        let code: u64 = item["code"].as_u64().unwrap_or_default();
 
-        if in_sequence && ![401, 405].contains(&code) {
-            if !line.is_empty() {
-                let mut joined: String = line.join("\n").trim().replace('\n', r"\#");
+        if let Some((continuation, kind)) = sequence {
+            if code != continuation || !allowed_codes.contains(&code) {
+                if !line.is_empty() {
+                    let mut joined: String = join_sequence_lines(line.iter().map(String::as_str)).replace('\n', r"\#");
 
-                if romanize {
-                    joined = romanize_string(joined);
-                }
+                    if romanize {
+                        joined = romanize_string(joined);
+                    }
+
+                    let parsed: Option<String> = parse_parameter(kind, &joined, section, game_profile, rules);
+
+                    if let Some(mut parsed) = parsed {
+                        if placeholders {
+                            parsed = protect_codes(&parsed).0;
+                        }
 
-                let parsed: Option<String> = parse_parameter(Code::Dialogue, &joined, game_type);
+                        if let (Some(comments), Some(speaker)) = (comments.as_deref_mut(), &line_speaker) {
+                            // Speaker is tagged as in a screenplay, so the comment doesn't depend on the language
+                            let comment: String = format!("{speaker}:");
+                            let line_comments: &mut Vec<String> = comments.entry(parsed.clone()).or_default();
+
+                            if !line_comments.contains(&comment) {
+                                line_comments.push(comment);
+                            }
+                        }
 
-                if let Some(parsed) = parsed {
-                    if processing_mode == ProcessingMode::Append && !map.contains_key(&joined) {
-                        map.shift_insert(set.len(), parsed.clone(), "".into());
+                        insert_line(parsed, format!("{location}:{line_start}"), processing_mode, lines, map);
                     }
 
-                    set.insert(parsed);
+                    line.clear();
                 }
 
-                line.clear();
+                sequence = None;
             }
-
-            in_sequence = false;
         }
 
         if !allowed_codes.contains(&code) {
             continue;
         }
 
-        let parameters: &Array = item["parameters"].as_array().unwrap();
+        let Some(rule) = get_code_rule(code) else {
+            continue;
+        };
+
+        let Some(parameters) = item["parameters"].as_array() else {
+            continue;
+        };
 
-        if [401, 405].contains(&code) {
-            if let Some(parameter_str) = parameters[0].as_str() {
-                if !parameter_str.is_empty() {
-                    in_sequence = true;
-                    line.push(parameter_str.trim().to_string()); // Maybe this shouldn't be trimmed
+        match rule.extraction {
+            Extraction::Sequence {
+                parameter,
+                continuation,
+            } => {
+                if sequence.is_none() {
+                    line_speaker = header_speaker.filter(|_| code == 401);
                 }
+
+                if let Some(parameter_str) = parameters.get(parameter).and_then(|value: &Value| value.as_str()) {
+                    if !parameter_str.is_empty() {
+                        if line.is_empty() {
+                            line_start = it;
+                        }
+
+                        line.push(parameter_str.to_string());
+                    }
+                }
+
+                sequence = Some((continuation, rule.kind));
             }
-        } else if parameters[0].is_array() {
-            for i in 0..parameters[0].as_array().unwrap().len() {
-                if let Some(subparameter_str) = parameters[0][i].as_str() {
-                    if !subparameter_str.is_empty() {
-                        let parsed: Option<String> = parse_parameter(Code::Choice, subparameter_str, game_type);
+            Extraction::Array(parameter) => {
+                let Some(array) = parameters.get(parameter).and_then(|value: &Value| value.as_array()) else {
+                    continue;
+                };
 
-                        if let Some(mut parsed) = parsed {
-                            if romanize {
-                                parsed = romanize_string(parsed);
-                            }
+                for subparameter in array.iter() {
+                    if let Some(subparameter_str) = subparameter.as_str() {
+                        if !subparameter_str.is_empty() {
+                            let parsed: Option<String> =
+                                parse_parameter(rule.kind, subparameter_str, section, game_profile, rules);
 
-                            if processing_mode == ProcessingMode::Append && !map.contains_key(&parsed) {
-                                map.shift_insert(set.len(), parsed.clone(), "".into());
-                            }
+                            if let Some(mut parsed) = parsed {
+                                if romanize {
+                                    parsed = romanize_string(parsed);
+                                }
+
+                                if placeholders {
+                                    parsed = protect_codes(&parsed).0;
+                                }
 
-                            set.insert(parsed);
+                                insert_line(parsed, format!("{location}:{it}"), processing_mode, lines, map);
+                            }
                         }
                     }
                 }
             }
-        } else if let Some(parameter_str) = parameters[0].as_str() {
-            if !parameter_str.is_empty() {
-                let parsed: Option<String> = parse_parameter(Code::System, parameter_str, game_type);
+            Extraction::Parameter(parameter) => {
+                let Some(parameter_str) = parameters.get(parameter).and_then(|value: &Value| value.as_str()) else {
+                    continue;
+                };
+
+                if parameter_str.trim().is_empty() {
+                    continue;
+                }
+
+                let parsed: Option<String> =
+                    parse_parameter(rule.kind, parameter_str.trim(), section, game_profile, rules);
 
                 if let Some(mut parsed) = parsed {
                     if romanize {
                         parsed = romanize_string(parsed);
                     }
 
-                    if processing_mode == ProcessingMode::Append && !map.contains_key(&parsed) {
-                        map.shift_insert(set.len(), parsed.clone(), "".into());
+                    if placeholders {
+                        parsed = protect_codes(&parsed).0;
                     }
 
-                    set.insert(parsed);
+                    // Speaker names are parsed to a separate section
+                    if rule.kind == Code::Speaker {
+                        speaker = Some(parsed.clone());
+                        insert_line(
+                            parsed,
+                            format!("{location}:{it}"),
+                            speakers_processing_mode,
+                            speakers_lines,
+                            speakers_map,
+                        );
+                    } else {
+                        insert_line(parsed, format!("{location}:{it}"), processing_mode, lines, map);
+                    }
                 }
             }
-        } else if let Some(parameter_str) = parameters[1].as_str() {
-            if !parameter_str.is_empty() {
-                let parsed: Option<String> = parse_parameter(Code::Unknown, parameter_str, game_type);
+            Extraction::Script => {
+                let Some(script) = parameters.first().and_then(|value: &Value| value.as_str()) else {
+                    continue;
+                };
 
-                if let Some(mut parsed) = parsed {
-                    if romanize {
-                        parsed = romanize_string(parsed);
+                // Escape codes of literals are escaped by JavaScript, so literals aren't protected with placeholders
+                for (_, literal) in script_string_literals(script) {
+                    if rules.skips_parameter(section, rule.kind, literal) {
+                        continue;
                     }
 
-                    if processing_mode == ProcessingMode::Append && !map.contains_key(&parsed) {
-                        map.shift_insert(set.len(), parsed.clone(), "".into());
+                    if let Some(mut parsed) = parse_plugin_parameter(literal) {
+                        if romanize {
+                            parsed = romanize_string(parsed);
+                        }
+
+                        insert_line(parsed, format!("{location}:{it}"), processing_mode, lines, map);
                     }
+                }
+            }
+            Extraction::PluginArguments => {
+                let Some(arguments) = parameters.get(3) else {
+                    continue;
+                };
 
-                    set.insert(parsed);
+                // Arguments may be nested JSON strings, so, like script literals, they aren't protected with placeholders
+                let mut parsed_strings: Vec<String> = Vec::new();
+                parse_plugin_value(arguments, &mut parsed_strings);
+                parsed_strings.retain(|parsed: &String| !rules.skips_parameter(section, rule.kind, parsed));
+
+                for mut parsed in parsed_strings {
+                    if romanize {
+                        parsed = romanize_string(parsed);
+                    }
+
+                    insert_line(parsed, format!("{location}:{it}"), processing_mode, lines, map);
                 }
             }
+            Extraction::PluginArgumentText => {}
         }
     }
 }
-// ! In current implementation, function performs extremely inefficient inserting of owned string to both hashmap and a hashset
-/// Reads all Map .json files of maps_path and parses them into .txt files in output_path.
+
+/// Returns the name of the translation section of a separate map, e.g. `Map001_Forest` for `Map001.json` with `Forest`
+/// display name. Symbols, that can't be used in file names, are removed from the display name.
+fn map_section_name(filename: &str, display_name: &str) -> String {
+    let stem: &str = filename.strip_suffix(".json").unwrap_or(filename);
+    let display_name: String = display_name
+        .chars()
+        .filter(|char: &char| !char.is_control() && !r#"<>:"/\|?*."#.contains(*char))
+        .collect::<String>()
+        .trim()
+        .to_string();
+
+    if display_name.is_empty() {
+        stem.to_string()
+    } else {
+        format!("{stem}_{display_name}")
+    }
+}
+
+/// Removes translation files of the section, e.g. when the display name of the map, that is a part of their name, is
+/// changed.
+fn remove_section_files(output_path: &Path, name: &str, format: Format) -> Result<(), Error> {
+    let extensions: &[&str] = match format {
+        Format::Txt => &[".txt", "_trans.txt", ".locations.json"],
+        Format::Po => &[".po", ".locations.json"],
+    };
+
+    for extension in extensions {
+        let file_path: &Path = &output_path.join(format!("{name}{extension}"));
+
+        if file_path.exists() {
+            remove_file(file_path).map_err(|error| Error::Io(file_path.to_path_buf(), error))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads all Map .json files of maps_path and parses them into translation files in output_path.
 /// # Parameters
 /// * `maps_path` - path to directory than contains .json game files
 /// * `output_path` - path to output directory
@@ -349,8 +588,17 @@ fn parse_list<T: BuildHasher>(
 /// * `file_parsed_msg` - message to log when file is parsed
 /// * `file_already_parsed_msg` - message to log when file that's about to be parsed already exists (default processing mode)
 /// * `file_is_not_parsed_msg` - message to log when file that's about to be parsed not exist (append processing mode)
-/// * `game_type` - game type for custom parsing
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
 /// * `processing_mode` - whether to read in default mode, force rewrite or append new text to existing files
+/// * `format` - format of translation files
+/// * `contextual_keys` - whether to add location-specific entries for lines, that occur in several locations
+/// * `locations` - whether to write locations of every line to a .locations.json file
+/// * `split_maps` - whether to write a separate translation file for every map instead of a single one
+/// * `global_dedup` - whether to pre-fill translation of lines in separate maps with their translation from other maps
+/// * `speaker_context` - whether to add speaker names of dialogue lines as comments to .po files
+/// * `codes` - codes of the event commands, which text is extracted
+/// * `placeholders` - whether to replace escape codes of event commands text with placeholders
 pub fn read_map(
     maps_path: &Path,
     output_path: &Path,
@@ -359,93 +607,102 @@ pub fn read_map(
     file_parsed_msg: &str,
     file_already_parsed_msg: &str,
     file_is_not_parsed_msg: &str,
-    game_type: &Option<GameType>,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
     mut processing_mode: &ProcessingMode,
-) {
-    let maps_output_path: &Path = &output_path.join("maps.txt");
-    let maps_trans_output_path: &Path = &output_path.join("maps_trans.txt");
-    let names_output_path: &Path = &output_path.join("names.txt");
-    let names_trans_output_path: &Path = &output_path.join("names_trans.txt");
-
-    if processing_mode == ProcessingMode::Default && maps_trans_output_path.exists() {
-        println!("maps_trans.txt {file_already_parsed_msg}");
-        return;
-    }
-
-    let maps_files: Vec<DirEntry> = read_dir(maps_path)
-        .unwrap()
-        .filter_map(|entry: Result<DirEntry, _>| match entry {
-            Ok(entry) => {
-                let filename_os_string: OsString = entry.file_name();
-                let filename: &str = unsafe { from_utf8_unchecked(filename_os_string.as_encoded_bytes()) };
-
-                let slice: char;
-                unsafe {
-                    slice = *filename.as_bytes().get_unchecked(4) as char;
-                }
-
-                if filename.starts_with("Map") && slice.is_ascii_digit() && filename.ends_with("json") {
-                    Some(entry)
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        })
-        .collect();
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+    split_maps: bool,
+    global_dedup: bool,
+    speaker_context: bool,
+    codes: &[u64],
+    placeholders: bool,
+) -> Vec<Error> {
+    // When maps are split, every map has its own translation file, so the translation file of names is checked instead
+    let checked_section_name: &str = if split_maps { "names" } else { "maps" };
+    let checked_translation_file_name: String = translation_file_name(checked_section_name, format);
+
+    if processing_mode == ProcessingMode::Default && output_path.join(&checked_translation_file_name).exists() {
+        println!("{checked_translation_file_name} {file_already_parsed_msg}");
+        return Vec::new();
+    }
 
-    let maps_obj_vec: Vec<(String, Object)> = maps_files
-        .into_iter()
-        .map(|entry: DirEntry| {
-            (
-                unsafe { from_utf8_unchecked(entry.file_name().as_encoded_bytes()).to_string() },
-                from_str(&read_to_string(entry.path()).unwrap()).unwrap(),
-            )
-        })
-        .collect();
+    let mut errors: Vec<Error> = Vec::new();
+    let maps_obj_vec: Vec<(String, Object)> = read_game_files(maps_path, is_map_file, &mut errors);
 
-    let mut maps_lines: IndexSet<String, BuildHasherDefault<Xxh3>> = IndexSet::default();
-    let mut names_lines: IndexSet<String, BuildHasherDefault<Xxh3>> = IndexSet::default();
+    let mut maps_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut names_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
 
     let mut maps_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
     let mut names_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
 
     if processing_mode == ProcessingMode::Append {
-        if maps_trans_output_path.exists() {
-            for (original, translated) in read_to_string(maps_output_path)
-                .unwrap()
-                .par_split('\n')
-                .collect::<Vec<_>>()
-                .into_iter()
-                .zip(
-                    read_to_string(maps_trans_output_path)
-                        .unwrap()
-                        .par_split('\n')
-                        .collect::<Vec<_>>()
-                        .into_iter(),
-                )
-            {
-                maps_translation_map.insert(original.to_string(), translated.to_string());
+        match read_translation_map(output_path, checked_section_name, format) {
+            Ok(Some(translation_map)) => {
+                if split_maps {
+                    names_translation_map = translation_map;
+                } else {
+                    maps_translation_map = translation_map;
+
+                    match read_translation_map(output_path, "names", format) {
+                        Ok(translation_map) => names_translation_map = translation_map.unwrap_or_default(),
+                        Err(error) => return vec![error],
+                    }
+                }
             }
+            Ok(None) => {
+                println!("{file_is_not_parsed_msg}");
+                processing_mode = &ProcessingMode::Default;
+            }
+            Err(error) => return vec![error],
+        }
+    }
 
-            for (original, translated) in read_to_string(names_output_path)
-                .unwrap()
-                .split('\n')
-                .zip(read_to_string(names_trans_output_path).unwrap().split('\n'))
-            {
-                names_translation_map.insert(original.to_string(), translated.to_string());
+    let mut speakers_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut speakers_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut speakers_processing_mode: &ProcessingMode = processing_mode;
+
+    if processing_mode == ProcessingMode::Append {
+        match read_translation_map(output_path, "speakers", format) {
+            Ok(Some(translation_map)) => speakers_translation_map = translation_map,
+            Ok(None) => speakers_processing_mode = &ProcessingMode::Default,
+            Err(error) => return vec![error],
+        }
+    }
+
+    let mut maps_comments: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+    let map_sections: HashMap<String, String> = if split_maps {
+        match find_map_sections(output_path, format) {
+            Ok(map_sections) => map_sections,
+            Err(error) => return vec![error],
+        }
+    } else {
+        HashMap::new()
+    };
+
+    // Translations of all maps, including the ones from a single translation file, if maps weren't split before
+    let mut global_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> = HashMap::default();
+
+    if global_dedup && processing_mode == ProcessingMode::Append {
+        for section_name in std::iter::once("maps").chain(map_sections.values().map(String::as_str)) {
+            let translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> =
+                match read_translation_map(output_path, section_name, format) {
+                    Ok(translation_map) => translation_map.unwrap_or_default(),
+                    Err(error) => return vec![error],
+                };
+
+            for (line, translated) in translation_map {
+                if !translated.is_empty() {
+                    global_translation_map.entry(line).or_insert(translated);
+                }
             }
-        } else {
-            println!("{file_is_not_parsed_msg}");
-            processing_mode = &ProcessingMode::Default;
         }
     }
 
-    // 401 - dialogue lines
-    // 102 - dialogue choices array
-    // 356 - system lines (special texts)
-    // 324 - i don't know what is it but it's some used in-game lines
-    const ALLOWED_CODES: [u64; 4] = [401, 102, 356, 324];
+    // Commands, which text is parsed, are described by the table in codes module, shared by reading and writing
+    let allowed_codes: Vec<u64> = parsed_codes(codes);
 
     for (filename, obj) in maps_obj_vec.into_iter() {
         if let Some(display_name) = obj["displayName"].as_str() {
@@ -456,32 +713,123 @@ pub fn read_map(
                     display_name_string = romanize_string(display_name_string);
                 }
 
-                if processing_mode == ProcessingMode::Append
-                    && !names_translation_map.contains_key(&display_name_string)
+                insert_line(
+                    display_name_string,
+                    format!("{filename}:displayName"),
+                    processing_mode,
+                    &mut names_lines,
+                    &mut names_translation_map,
+                );
+            }
+        }
+
+        let mut map_processing_mode: &ProcessingMode = processing_mode;
+        let existing_section_name: Option<&String> =
+            map_sections.get(filename.strip_suffix(".json").unwrap_or(&filename));
+
+        if split_maps {
+            maps_translation_map = IndexMap::default();
+
+            if processing_mode == ProcessingMode::Append {
+                match existing_section_name
+                    .map(|section_name: &String| read_translation_map(output_path, section_name, format))
+                    .transpose()
+                    .map(Option::flatten)
                 {
-                    names_translation_map.shift_insert(names_lines.len(), display_name_string.clone(), "".into());
+                    Ok(Some(translation_map)) => maps_translation_map = translation_map,
+                    Ok(None) => map_processing_mode = &ProcessingMode::Default,
+                    // Lines of the map aren't written, so its existing translation is kept
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    }
                 }
-
-                names_lines.insert(display_name_string);
             }
         }
 
+        let Some(events) = obj["events"].as_array() else {
+            continue;
+        };
+
         //Skipping first element in array as it is null
-        for event in obj["events"].as_array().unwrap().iter().skip(1) {
-            if !event["pages"].is_array() {
+        for (event_index, event) in events.iter().enumerate().skip(1) {
+            let Some(pages) = event["pages"].as_array() else {
                 continue;
-            }
+            };
+
+            let event_id: u64 = event["id"].as_u64().unwrap_or(event_index as u64);
+
+            for (page_index, page) in pages.iter().enumerate() {
+                let Some(list) = page["list"].as_array() else {
+                    continue;
+                };
 
-            for page in event["pages"].as_array().unwrap().iter() {
                 parse_list(
-                    page["list"].as_array().unwrap(),
-                    &ALLOWED_CODES,
+                    list,
+                    &allowed_codes,
+                    &format!("{filename}:{event_id}:{page_index}"),
                     romanize,
-                    game_type,
-                    processing_mode,
+                    placeholders,
+                    Section::Maps,
+                    game_profile,
+                    rules,
+                    map_processing_mode,
                     &mut maps_lines,
                     &mut maps_translation_map,
+                    speakers_processing_mode,
+                    &mut speakers_lines,
+                    &mut speakers_translation_map,
+                    speaker_context.then_some(&mut maps_comments),
+                );
+            }
+        }
+
+        if split_maps {
+            let section_name: String = map_section_name(&filename, obj["displayName"].as_str().unwrap_or_default());
+
+            if let Some(existing_section_name) = existing_section_name {
+                if *existing_section_name != section_name {
+                    if let Err(error) = remove_section_files(output_path, existing_section_name, format) {
+                        errors.push(error);
+                    }
+                }
+            }
+
+            if global_dedup && !global_translation_map.is_empty() {
+                if map_processing_mode == ProcessingMode::Default {
+                    maps_translation_map = maps_lines
+                        .keys()
+                        .map(|line: &String| (line.clone(), String::new()))
+                        .collect();
+                    map_processing_mode = &ProcessingMode::Append;
+                }
+
+                for (line, translated) in maps_translation_map.iter_mut() {
+                    if translated.is_empty() {
+                        if let Some(global_translated) = global_translation_map.get(line) {
+                            translated.clone_from(global_translated);
+                        }
+                    }
+                }
+            }
+
+            if !maps_lines.is_empty() || !maps_translation_map.is_empty() {
+                let result: Result<(), Error> = write_lines(
+                    output_path,
+                    &section_name,
+                    format,
+                    contextual_keys,
+                    locations,
+                    map_processing_mode,
+                    std::mem::take(&mut maps_lines),
+                    std::mem::take(&mut maps_translation_map),
+                    speaker_context.then(|| std::mem::take(&mut maps_comments)),
                 );
+
+                if let Err(error) = result {
+                    errors.push(error);
+                    continue;
+                }
             }
         }
 
@@ -490,33 +838,53 @@ pub fn read_map(
         }
     }
 
-    let (maps_original_content, maps_translated_content, names_original_content, names_translated_content) =
-        if processing_mode == ProcessingMode::Append {
-            let maps_collected: (Vec<String>, Vec<String>) = maps_translation_map.into_iter().unzip();
-            let names_collected: (Vec<String>, Vec<String>) = names_translation_map.into_iter().unzip();
-            (
-                maps_collected.0.join("\n"),
-                maps_collected.1.join("\n"),
-                names_collected.0.join("\n"),
-                names_collected.1.join("\n"),
-            )
-        } else {
-            (
-                maps_lines.join("\n"),
-                "\n".repeat(maps_lines.len().saturating_sub(1)),
-                names_lines.join("\n"),
-                "\n".repeat(names_lines.len().saturating_sub(1)),
-            )
-        };
+    let mut results: Vec<Result<(), Error>> = Vec::new();
+
+    if !split_maps {
+        results.push(write_lines(
+            output_path,
+            "maps",
+            format,
+            contextual_keys,
+            locations,
+            processing_mode,
+            maps_lines,
+            maps_translation_map,
+            speaker_context.then_some(maps_comments),
+        ));
+    }
 
-    write(maps_output_path, maps_original_content).unwrap();
-    write(maps_trans_output_path, maps_translated_content).unwrap();
-    write(names_output_path, names_original_content).unwrap();
-    write(names_trans_output_path, names_translated_content).unwrap();
+    results.push(write_lines(
+        output_path,
+        "names",
+        format,
+        contextual_keys,
+        locations,
+        processing_mode,
+        names_lines,
+        names_translation_map,
+        None,
+    ));
+
+    if !speakers_lines.is_empty() || !speakers_translation_map.is_empty() {
+        results.push(write_lines(
+            output_path,
+            "speakers",
+            format,
+            contextual_keys,
+            locations,
+            speakers_processing_mode,
+            speakers_lines,
+            speakers_translation_map,
+            None,
+        ));
+    }
+
+    errors.extend(results.into_iter().filter_map(Result::err));
+    errors
 }
 
-// ! In current implementation, function performs extremely inefficient inserting of owned string to both hashmap and a hashset
-/// Reads all Other .json files of other_path and parses them into .txt files in output_path.
+/// Reads all Other .json files of other_path and parses them into translation files in output_path.
 /// # Parameters
 /// * `other_path` - path to directory than contains .json game files
 /// * `output_path` - path to output directory
@@ -525,8 +893,15 @@ pub fn read_map(
 /// * `file_parsed_msg` - message to log when file is parsed
 /// * `file_already_parsed_msg` - message to log when file that's about to be parsed already exists (default processing mode)
 /// * `file_is_not_parsed_msg` - message to log when file that's about to be parsed not exist (append processing mode)
-/// * `game_type` - game type for custom parsing
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
 /// * `processing_mode` - whether to read in default mode, force rewrite or append new text to existing files
+/// * `format` - format of translation files
+/// * `contextual_keys` - whether to add location-specific entries for lines, that occur in several locations
+/// * `locations` - whether to write locations of every line to a .locations.json file
+/// * `speaker_context` - whether to add speaker names of dialogue lines as comments to .po files
+/// * `codes` - codes of the event commands, which text is extracted
+/// * `placeholders` - whether to replace escape codes of event commands text with placeholders
 pub fn read_other(
     other_path: &Path,
     output_path: &Path,
@@ -535,134 +910,107 @@ pub fn read_other(
     file_parsed_msg: &str,
     file_already_parsed_msg: &str,
     file_is_not_parsed_msg: &str,
-    game_type: &Option<GameType>,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
     processing_mode: &ProcessingMode,
-) {
-    let other_files: Vec<DirEntry> = read_dir(other_path)
-        .unwrap()
-        .filter_map(|entry: Result<DirEntry, _>| match entry {
-            Ok(entry) => {
-                let filename_os_string: OsString = entry.file_name();
-                let filename: &str = unsafe { from_utf8_unchecked(filename_os_string.as_encoded_bytes()) };
-                let (real_name, extension) = filename.split_once('.').unwrap();
-
-                if !real_name.starts_with("Map")
-                    && !matches!(real_name, "Tilesets" | "Animations" | "States" | "System")
-                    && extension == "json"
-                {
-                    Some(entry)
-                } else {
-                    None
-                }
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+    speaker_context: bool,
+    codes: &[u64],
+    placeholders: bool,
+) -> Vec<Error> {
+    let mut errors: Vec<Error> = Vec::new();
+    let other_obj_arr_map: Vec<(String, Array)> = read_game_files(other_path, is_other_file, &mut errors);
+
+    // Speakers of common events and troops are parsed to a single section
+    let speakers_file_exists: bool = output_path.join(translation_file_name("speakers", format)).exists();
+
+    let mut speakers_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut speakers_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut speakers_processing_mode: &ProcessingMode = processing_mode;
+
+    if processing_mode == ProcessingMode::Append {
+        match read_translation_map(output_path, "speakers", format) {
+            Ok(Some(translation_map)) => speakers_translation_map = translation_map,
+            Ok(None) => speakers_processing_mode = &ProcessingMode::Default,
+            Err(error) => {
+                errors.push(error);
+                return errors;
             }
-            Err(_) => None,
-        })
-        .collect();
-
-    let other_obj_arr_map: Vec<(String, Array)> = other_files
-        .into_par_iter()
-        .map(|entry: DirEntry| {
-            (
-                unsafe { from_utf8_unchecked(entry.file_name().as_encoded_bytes()).to_string() },
-                from_str(&read_to_string(entry.path()).unwrap()).unwrap(),
-            )
-        })
-        .collect();
-
-    let mut inner_processing_type: &ProcessingMode = processing_mode;
-
-    // 401 - dialogue lines
-    // 405 - credits lines
-    // 102 - dialogue choices array
-    // 356 - system lines (special texts)
-    // 324 - i don't know what is it but it's some used in-game lines
-    const ALLOWED_CODES: [u64; 5] = [401, 405, 356, 102, 324];
+        }
+    }
 
-    for (filename, obj_arr) in other_obj_arr_map.into_iter() {
-        let other_processed_filename: String = filename[0..filename.rfind('.').unwrap()].to_lowercase();
+    // Commands, which text is parsed, are described by the table in codes module, shared by reading and writing
+    let allowed_codes: Vec<u64> = parsed_codes(codes);
 
-        let other_output_path: &Path = &output_path.join(other_processed_filename.clone() + ".txt");
-        let other_trans_output_path: &Path = &output_path.join(other_processed_filename + "_trans.txt");
+    for (filename, obj_arr) in other_obj_arr_map.into_iter() {
+        let other_processed_filename: String = filename[..filename.len() - 5].to_lowercase();
+        let other_translation_file_name: String = translation_file_name(&other_processed_filename, format);
 
-        if processing_mode == ProcessingMode::Default && other_trans_output_path.exists() {
-            println!("{} {file_already_parsed_msg}", unsafe {
-                from_utf8_unchecked(other_trans_output_path.file_name().unwrap().as_encoded_bytes())
-            });
+        if processing_mode == ProcessingMode::Default && output_path.join(&other_translation_file_name).exists() {
+            println!("{other_translation_file_name} {file_already_parsed_msg}");
             continue;
         }
 
-        let mut other_lines: IndexSet<String, BuildHasherDefault<Xxh3>> = IndexSet::default();
+        let mut other_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
         let mut other_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut other_comments: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+        // Each file falls back to default mode on its own, when its translation file is missing
+        let mut inner_processing_type: &ProcessingMode = processing_mode;
 
         if processing_mode == ProcessingMode::Append {
-            if other_trans_output_path.exists() {
-                for (original, translated) in read_to_string(other_output_path)
-                    .unwrap()
-                    .par_split('\n')
-                    .collect::<Vec<_>>()
-                    .into_iter()
-                    .zip(
-                        read_to_string(other_trans_output_path)
-                            .unwrap()
-                            .par_split('\n')
-                            .collect::<Vec<_>>()
-                            .into_iter(),
-                    )
-                {
-                    other_translation_map.insert(original.to_string(), translated.to_string());
+            match read_translation_map(output_path, &other_processed_filename, format) {
+                Ok(Some(translation_map)) => other_translation_map = translation_map,
+                Ok(None) => {
+                    println!("{file_is_not_parsed_msg}");
+                    inner_processing_type = &ProcessingMode::Default;
+                }
+                Err(error) => {
+                    errors.push(error);
+                    continue;
                 }
-            } else {
-                println!("{file_is_not_parsed_msg}");
-                inner_processing_type = &ProcessingMode::Default;
             }
         }
 
-        // Other files except CommonEvents.json and Troops.json have the structure that consists
-        // of name, nickname, description and note
-        if !filename.starts_with("Co") && !filename.starts_with("Tr") {
-            if game_type
-                .as_ref()
-                .is_some_and(|game_type| *game_type == GameType::Termina)
-                && filename.starts_with("It")
-            {
-                for string in [
-                    "<Menu Category: Items>",
-                    "<Menu Category: Food>",
-                    "<Menu Category: Healing>",
-                    "<Menu Category: Body bag>",
-                ] {
-                    other_lines.insert(string.to_string());
-                }
-            }
+        // Fields of the objects, that are parsed, are described by the table in the crate root, shared by reading and
+        // writing
+        let fields: &DatabaseFields = database_fields(&filename);
 
+        // Other files except CommonEvents.json and Troops.json consist of objects with fields only
+        if !filename.starts_with("Co") && !filename.starts_with("Tr") {
             'obj: for obj in obj_arr {
                 let mut prev_variable_type: Option<Variable> = None;
+                let id: u64 = obj["id"].as_u64().unwrap_or_default();
 
-                for (variable_text, variable_type) in [
-                    (obj["name"].as_str(), Variable::Name),
-                    (obj["nickname"].as_str(), Variable::Nickname),
-                    (obj["description"].as_str(), Variable::Description),
-                    (obj["message1"].as_str(), Variable::Message1),
-                    (obj["message2"].as_str(), Variable::Message2),
-                    (obj["message3"].as_str(), Variable::Message3),
-                    (obj["message4"].as_str(), Variable::Message4),
-                    (obj["note"].as_str(), Variable::Note),
-                ] {
-                    if let Some(mut variable_str) = variable_text {
+                for &(variable_label, variable_type) in fields.fields {
+                    if let Some(mut variable_str) = obj[variable_label].as_str() {
                         variable_str = variable_str.trim();
 
+                        // Notes with tags are parsed only through their tags, unless they continue descriptions
+                        if variable_type == Variable::Note
+                            && rules.has_note_tags(&filename, game_profile)
+                            && game_profile
+                                .as_ref()
+                                .and_then(|game_profile: &GameProfile| game_profile.note_continuation(&filename))
+                                .is_none()
+                        {
+                            continue;
+                        }
+
                         if !variable_str.is_empty() {
                             let parsed: Option<(String, bool)> =
-                                parse_variable(variable_str.to_string(), &variable_type, &filename, game_type);
+                                parse_variable(variable_str, &variable_type, &filename, game_profile, rules);
 
-                            if let Some((mut parsed, is_continuation_of_description)) = parsed {
+                            if let Some((parsed, is_continuation_of_description)) = parsed {
                                 if is_continuation_of_description {
                                     if prev_variable_type != Some(Variable::Description) {
                                         continue;
                                     }
 
-                                    if let Some(last) = other_lines.pop() {
-                                        other_lines.insert(last + &parsed);
+                                    if let Some((last, locations)) = other_lines.pop() {
+                                        other_lines.insert(last + &parsed, locations);
                                     }
 
                                     if inner_processing_type == ProcessingMode::Append {
@@ -676,25 +1024,63 @@ pub fn read_other(
 
                                 prev_variable_type = Some(variable_type);
 
-                                if romanize {
-                                    parsed = romanize_string(parsed);
-                                }
+                                insert_field(
+                                    parsed,
+                                    format!("{filename}:{id}:{variable_label}"),
+                                    romanize,
+                                    inner_processing_type,
+                                    &mut other_lines,
+                                    &mut other_translation_map,
+                                );
+                            } else if variable_type == Variable::Name {
+                                continue 'obj;
+                            }
+                        }
+                    }
+                }
 
-                                let replaced: String = parsed
-                                    .split('\n')
-                                    .map(|line: &str| line.trim())
-                                    .collect::<Vec<_>>()
-                                    .join(r"\#");
+                let note: &str = obj["note"].as_str().unwrap_or_default();
 
-                                if inner_processing_type == ProcessingMode::Append
-                                    && !other_translation_map.contains_key(&replaced)
-                                {
-                                    other_translation_map.shift_insert(other_lines.len(), replaced.clone(), "".into());
-                                }
+                for range in rules.note_tag_ranges(&filename, note, game_profile) {
+                    let text: &str = &note[range];
 
-                                other_lines.insert(replaced);
-                            } else if variable_type == Variable::Name {
-                                continue 'obj;
+                    if STRING_IS_ONLY_SYMBOLS_RE.is_match(text) || rules.skips_variable(&Variable::Note, text) {
+                        continue;
+                    }
+
+                    insert_field(
+                        text.to_string(),
+                        format!("{filename}:{id}:note"),
+                        romanize,
+                        inner_processing_type,
+                        &mut other_lines,
+                        &mut other_translation_map,
+                    );
+                }
+
+                for &(array_label, nested_fields) in fields.nested {
+                    let Some(array) = obj[array_label].as_array() else {
+                        continue;
+                    };
+
+                    for (i, nested_obj) in array.iter().enumerate() {
+                        for &(variable_label, variable_type) in nested_fields {
+                            let Some(variable_str) = nested_obj[variable_label].as_str() else {
+                                continue;
+                            };
+
+                            if let Some(parsed) =
+                                parse_field(variable_str.trim(), &variable_type, &filename, game_profile, rules)
+                                    .filter(|parsed: &String| !parsed.is_empty())
+                            {
+                                insert_field(
+                                    parsed,
+                                    format!("{filename}:{id}:{array_label}:{i}:{variable_label}"),
+                                    romanize,
+                                    inner_processing_type,
+                                    &mut other_lines,
+                                    &mut other_translation_map,
+                                );
                             }
                         }
                     }
@@ -704,56 +1090,113 @@ pub fn read_other(
         // Other files have the structure somewhat similar to Maps.json files
         else {
             // Skipping first element in array as it is null
-            for obj in obj_arr.into_iter().skip(1) {
+            for (obj_index, obj) in obj_arr.into_iter().enumerate().skip(1) {
+                let id: u64 = obj["id"].as_u64().unwrap_or(obj_index as u64);
+
+                for &(variable_label, variable_type) in fields.fields {
+                    let Some(variable_str) = obj[variable_label].as_str() else {
+                        continue;
+                    };
+
+                    if let Some(parsed) =
+                        parse_field(variable_str.trim(), &variable_type, &filename, game_profile, rules)
+                            .filter(|parsed: &String| !parsed.is_empty())
+                    {
+                        insert_field(
+                            parsed,
+                            format!("{filename}:{id}:{variable_label}"),
+                            romanize,
+                            inner_processing_type,
+                            &mut other_lines,
+                            &mut other_translation_map,
+                        );
+                    }
+                }
+
                 // CommonEvents doesn't have pages, so we can just check if it's Troops
                 let pages_length: usize = if filename.starts_with("Tr") {
-                    obj["pages"].as_array().unwrap().len()
+                    obj["pages"].as_array().map_or(0, |pages: &Array| pages.len())
                 } else {
                     1
                 };
 
                 for i in 0..pages_length {
-                    let list: &Value = if pages_length != 1 {
+                    let list_value: &Value = if filename.starts_with("Tr") {
                         &obj["pages"][i]["list"]
                     } else {
                         &obj["list"]
                     };
 
-                    if !list.is_array() {
+                    let Some(list) = list_value.as_array() else {
                         continue;
-                    }
+                    };
 
                     parse_list(
-                        list.as_array().unwrap(),
-                        &ALLOWED_CODES,
+                        list,
+                        &allowed_codes,
+                        &format!("{filename}:{id}:{i}"),
                         romanize,
-                        game_type,
-                        processing_mode,
+                        placeholders,
+                        Section::Other,
+                        game_profile,
+                        rules,
+                        inner_processing_type,
                         &mut other_lines,
                         &mut other_translation_map,
+                        speakers_processing_mode,
+                        &mut speakers_lines,
+                        &mut speakers_translation_map,
+                        speaker_context.then_some(&mut other_comments),
                     );
                 }
             }
         }
 
-        let (original_content, translation_content) = if processing_mode == ProcessingMode::Append {
-            let collected: (Vec<String>, Vec<String>) = other_translation_map.into_iter().unzip();
-            (collected.0.join("\n"), collected.1.join("\n"))
-        } else {
-            (other_lines.join("\n"), "\n".repeat(other_lines.len().saturating_sub(1)))
-        };
-
-        write(other_output_path, original_content).unwrap();
-        write(other_trans_output_path, translation_content).unwrap();
+        if let Err(error) = write_lines(
+            output_path,
+            &other_processed_filename,
+            format,
+            contextual_keys,
+            locations,
+            inner_processing_type,
+            other_lines,
+            other_translation_map,
+            speaker_context.then_some(other_comments),
+        ) {
+            errors.push(error);
+            continue;
+        }
 
         if logging {
             println!("{file_parsed_msg} {filename}");
         }
     }
+
+    if processing_mode == ProcessingMode::Default && speakers_file_exists {
+        println!(
+            "{} {file_already_parsed_msg}",
+            translation_file_name("speakers", format)
+        );
+    } else if !speakers_lines.is_empty() || !speakers_translation_map.is_empty() {
+        if let Err(error) = write_lines(
+            output_path,
+            "speakers",
+            format,
+            contextual_keys,
+            locations,
+            speakers_processing_mode,
+            speakers_lines,
+            speakers_translation_map,
+            None,
+        ) {
+            errors.push(error);
+        }
+    }
+
+    errors
 }
 
-// ! In current implementation, function performs extremely inefficient inserting of owned string to both hashmap and a hashset
-/// Reads System .json file of system_file_path and parses it into .txt file of output_path.
+/// Reads System .json file of system_file_path and parses it into translation file of output_path.
 /// # Parameters
 /// * `system_file_path` - path to directory than contains .json files
 /// * `output_path` - path to output directory
@@ -762,7 +1205,11 @@ pub fn read_other(
 /// * `file_parsed_msg` - message to log when file is parsed
 /// * `file_already_parsed_msg` - message to log when file that's about to be parsed already exists (default processing mode)
 /// * `file_is_not_parsed_msg` - message to log when file that's about to be parsed not exist (append processing mode)
+/// * `rules` - user-defined rules, that decide which strings are translatable
 /// * `processing_mode` - whether to read in default mode, force rewrite or append new text to existing files
+/// * `format` - format of translation files
+/// * `contextual_keys` - whether to add location-specific entries for lines, that occur in several locations
+/// * `locations` - whether to write locations of every line to a .locations.json file
 pub fn read_system(
     system_file_path: &Path,
     output_path: &Path,
@@ -771,164 +1218,122 @@ pub fn read_system(
     file_parsed_msg: &str,
     file_already_parsed_msg: &str,
     file_is_not_parsed_msg: &str,
+    rules: &Rules,
     mut processing_mode: &ProcessingMode,
-) {
-    let system_output_path: &Path = &output_path.join("system.txt");
-    let system_trans_output_path: &Path = &output_path.join("system_trans.txt");
-
-    if processing_mode == ProcessingMode::Default && system_trans_output_path.exists() {
-        println!("system_trans.txt {file_already_parsed_msg}");
-        return;
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+) -> Result<(), Error> {
+    let system_translation_file_name: String = translation_file_name("system", format);
+
+    if processing_mode == ProcessingMode::Default && output_path.join(&system_translation_file_name).exists() {
+        println!("{system_translation_file_name} {file_already_parsed_msg}");
+        return Ok(());
     }
 
-    let system_obj: Object = from_str(&read_to_string(system_file_path).unwrap()).unwrap();
+    let system_obj: Object = read_json(system_file_path)?;
 
-    let mut system_lines: IndexSet<String, BuildHasherDefault<Xxh3>> = IndexSet::default();
+    let mut system_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
     let mut system_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
 
     if processing_mode == ProcessingMode::Append {
-        if system_trans_output_path.exists() {
-            for (original, translated) in read_to_string(system_output_path)
-                .unwrap()
-                .par_split('\n')
-                .collect::<Vec<_>>()
-                .into_iter()
-                .zip(
-                    read_to_string(system_trans_output_path)
-                        .unwrap()
-                        .par_split('\n')
-                        .collect::<Vec<_>>()
-                        .into_iter(),
-                )
-            {
-                system_translation_map.insert(original.to_string(), translated.to_string());
-            }
-        } else {
-            println!("{file_is_not_parsed_msg}");
-            processing_mode = &ProcessingMode::Default;
-        }
-    }
-
-    // Armor types names
-    // Normally it's system strings, but might be needed for some purposes
-    for string in system_obj["armorTypes"].as_array().unwrap() {
-        let str: &str = string.as_str().unwrap().trim();
-
-        if !str.is_empty() {
-            let mut string: String = str.to_string();
-
-            if romanize {
-                string = romanize_string(string)
-            }
-
-            if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
-            }
-
-            system_lines.insert(string);
-        }
-    }
-
-    // Element types names
-    // Normally it's system strings, but might be needed for some purposes
-    for string in system_obj["elements"].as_array().unwrap() {
-        let str: &str = string.as_str().unwrap().trim();
-
-        if !str.is_empty() {
-            let mut string: String = str.to_string();
-
-            if romanize {
-                string = romanize_string(string)
-            }
-
-            if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
+        match read_translation_map(output_path, "system", format)? {
+            Some(translation_map) => system_translation_map = translation_map,
+            None => {
+                println!("{file_is_not_parsed_msg}");
+                processing_mode = &ProcessingMode::Default;
             }
-
-            system_lines.insert(string);
         }
     }
 
-    // Names of equipment slots
-    for string in system_obj["equipTypes"].as_array().unwrap() {
-        let str: &str = string.as_str().unwrap().trim();
-
-        if !str.is_empty() {
-            let mut string: String = str.to_string();
-
-            if romanize {
-                string = romanize_string(string)
-            }
-
-            if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
-            }
-
-            system_lines.insert(string);
-        }
-    }
+    // Armor types names, element types names, names of equipment slots and names of battle options
+    // Armor and element types are normally system strings, but might be needed for some purposes
+    for key in ["armorTypes", "elements", "equipTypes", "skillTypes"] {
+        let array: &Array = system_obj
+            .get(&key)
+            .and_then(|value: &Value| value.as_array())
+            .ok_or_else(|| invalid_value(system_file_path, key))?;
 
-    // Names of battle options
-    for string in system_obj["skillTypes"].as_array().unwrap() {
-        let str: &str = string.as_str().unwrap().trim();
+        for (i, string) in array.iter().enumerate() {
+            let Some(str) = string.as_str().map(str::trim) else {
+                continue;
+            };
 
-        if !str.is_empty() {
-            let mut string: String = str.to_string();
+            if !str.is_empty() && !rules.skips_string(Section::System, str) {
+                let mut string: String = str.to_string();
 
-            if romanize {
-                string = romanize_string(string)
-            }
+                if romanize {
+                    string = romanize_string(string)
+                }
 
-            if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
+                insert_line(
+                    string,
+                    format!("System.json:{key}:{i}"),
+                    processing_mode,
+                    &mut system_lines,
+                    &mut system_translation_map,
+                );
             }
-
-            system_lines.insert(string);
         }
     }
 
     // Game terms vocabulary
-    for (key, value) in system_obj["terms"].as_object().unwrap() {
+    let terms: &Object = system_obj
+        .get(&"terms")
+        .and_then(|value: &Value| value.as_object())
+        .ok_or_else(|| invalid_value(system_file_path, "terms"))?;
+
+    for (key, value) in terms {
         if key != "messages" {
-            for string in value.as_array().unwrap() {
+            let Some(array) = value.as_array() else {
+                continue;
+            };
+
+            for (i, string) in array.iter().enumerate() {
                 if let Some(mut str) = string.as_str() {
                     str = str.trim();
 
-                    if !str.is_empty() {
+                    if !str.is_empty() && !rules.skips_string(Section::System, str) {
                         let mut string: String = str.to_string();
 
                         if romanize {
                             string = romanize_string(string)
                         }
 
-                        if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                            system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
-                        }
-
-                        system_lines.insert(string);
+                        insert_line(
+                            string,
+                            format!("System.json:terms:{key}:{i}"),
+                            processing_mode,
+                            &mut system_lines,
+                            &mut system_translation_map,
+                        );
                     }
                 }
             }
         } else {
-            if !value.is_object() {
+            let Some(messages) = value.as_object() else {
                 continue;
-            }
+            };
 
-            for (_, message_string) in value.as_object().unwrap().iter() {
-                let str: &str = message_string.as_str().unwrap().trim();
+            for (message_key, message_string) in messages.iter() {
+                let Some(str) = message_string.as_str().map(str::trim) else {
+                    continue;
+                };
 
-                if !str.is_empty() {
+                if !str.is_empty() && !rules.skips_string(Section::System, str) {
                     let mut string: String = str.to_string();
 
                     if romanize {
                         string = romanize_string(string)
                     }
 
-                    if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                        system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
-                    }
-
-                    system_lines.insert(string);
+                    insert_line(
+                        string,
+                        format!("System.json:terms:messages:{message_key}"),
+                        processing_mode,
+                        &mut system_lines,
+                        &mut system_translation_map,
+                    );
                 }
             }
         }
@@ -936,57 +1341,331 @@ pub fn read_system(
 
     // Weapon types names
     // Normally it's system strings, but might be needed for some purposes
-    for string in system_obj["weaponTypes"].as_array().unwrap() {
-        let str: &str = string.as_str().unwrap().trim();
+    let weapon_types: &Array = system_obj
+        .get(&"weaponTypes")
+        .and_then(|value: &Value| value.as_array())
+        .ok_or_else(|| invalid_value(system_file_path, "weaponTypes"))?;
 
-        if !str.is_empty() {
+    for (i, string) in weapon_types.iter().enumerate() {
+        let Some(str) = string.as_str().map(str::trim) else {
+            continue;
+        };
+
+        if !str.is_empty() && !rules.skips_string(Section::System, str) {
             let mut string: String = str.to_string();
 
             if romanize {
                 string = romanize_string(string)
             }
 
-            if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&string) {
-                system_translation_map.shift_insert(system_lines.len(), string.clone(), "".into());
-            }
-
-            system_lines.insert(string);
+            insert_line(
+                string,
+                format!("System.json:weaponTypes:{i}"),
+                processing_mode,
+                &mut system_lines,
+                &mut system_translation_map,
+            );
         }
     }
 
     // Game title, parsed just for fun
     // Translators may add something like "ELFISH TRANSLATION v1.0.0" to the title
     {
-        let mut game_title_string: String = system_obj["gameTitle"].as_str().unwrap().trim().to_string();
+        let mut game_title_string: String = system_obj
+            .get(&"gameTitle")
+            .and_then(|value: &Value| value.as_str())
+            .ok_or_else(|| invalid_value(system_file_path, "gameTitle"))?
+            .trim()
+            .to_string();
 
         if romanize {
             game_title_string = romanize_string(game_title_string)
         }
 
-        if processing_mode == ProcessingMode::Append && !system_translation_map.contains_key(&game_title_string) {
-            system_translation_map.shift_insert(system_lines.len(), game_title_string.clone(), "".into());
+        // Game title is always the last line, so it's moved to the end, even if it's already parsed
+        let mut locations: Vec<String> = system_lines.shift_remove(&game_title_string).unwrap_or_default();
+        locations.push("System.json:gameTitle".into());
+
+        if processing_mode == ProcessingMode::Append {
+            let translated: String = system_translation_map
+                .shift_remove(&game_title_string)
+                .unwrap_or_default();
+            system_translation_map.insert(game_title_string.clone(), translated);
         }
 
-        system_lines.insert(game_title_string);
+        system_lines.insert(game_title_string, locations);
     }
 
-    let (original_content, translated_content) = if processing_mode == ProcessingMode::Append {
-        let collected: (Vec<String>, Vec<String>) = system_translation_map.into_iter().unzip();
-        (collected.0.join("\n"), collected.1.join("\n"))
-    } else {
-        (
-            system_lines.join("\n"),
-            "\n".repeat(system_lines.len().saturating_sub(1)),
-        )
-    };
-
-    write(system_output_path, original_content).unwrap();
-    write(system_trans_output_path, translated_content).unwrap();
+    write_lines(
+        output_path,
+        "system",
+        format,
+        contextual_keys,
+        locations,
+        processing_mode,
+        system_lines,
+        system_translation_map,
+        None,
+    )?;
 
     if logging {
         println!("{file_parsed_msg} System.json.");
     }
+
+    Ok(())
 }
 
-// read_plugins is not implemented and will NEVER be, as plugins can differ from each other incredibly.
-// Change plugins.js with your own hands.
+fn parse_plugin_parameter(parameter: &str) -> Option<String> {
+    if parameter.trim().is_empty()
+        || STRING_IS_ONLY_SYMBOLS_RE.is_match(parameter)
+        || PLUGIN_PARAMETER_IS_NOT_TEXT_RE.is_match(parameter)
+        || matches!(parameter, "true" | "false" | "null")
+        || parameter.starts_with(['[', '{'])
+    {
+        return None;
+    }
+
+    Some(parameter.to_string())
+}
+
+/// Recursively walks plugin parameter value, decoding JSON-encoded structs and lists, and collects its strings.
+fn parse_plugin_value(value: &Value, parsed_strings: &mut Vec<String>) {
+    if let Some(str) = value.as_str() {
+        if let Some(decoded) = decode_plugin_parameter(str) {
+            parse_plugin_value(&decoded, parsed_strings);
+        } else if let Some(parsed) = parse_plugin_parameter(str) {
+            parsed_strings.push(parsed);
+        }
+    } else if let Some(array) = value.as_array() {
+        for value in array.iter() {
+            parse_plugin_value(value, parsed_strings);
+        }
+    } else if let Some(object) = value.as_object() {
+        for (_, value) in object.iter() {
+            parse_plugin_value(value, parsed_strings);
+        }
+    }
+}
+
+/// Reads plugins.js file of plugins_file_path and parses its plugins' parameters into translation files in output_path.
+/// # Parameters
+/// * `plugins_file_path` - path to the plugins.js file
+/// * `output_path` - path to output directory
+/// * `romanize` - whether to romanize text
+/// * `logging` - whether to log
+/// * `file_parsed_msg` - message to log when file is parsed
+/// * `file_already_parsed_msg` - message to log when file that's about to be parsed already exists (default processing mode)
+/// * `file_is_not_parsed_msg` - message to log when file that's about to be parsed not exist (append processing mode)
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `processing_mode` - whether to read in default mode, force rewrite or append new text to existing files
+/// * `format` - format of translation files
+/// * `contextual_keys` - whether to add location-specific entries for lines, that occur in several locations
+/// * `locations` - whether to write locations of every line to a .locations.json file
+pub fn read_plugins(
+    plugins_file_path: &Path,
+    output_path: &Path,
+    romanize: bool,
+    logging: bool,
+    file_parsed_msg: &str,
+    file_already_parsed_msg: &str,
+    file_is_not_parsed_msg: &str,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    mut processing_mode: &ProcessingMode,
+    format: Format,
+    contextual_keys: bool,
+    locations: bool,
+) -> Result<(), Error> {
+    let plugins_translation_file_name: String = translation_file_name("plugins", format);
+
+    if processing_mode == ProcessingMode::Default && output_path.join(&plugins_translation_file_name).exists() {
+        println!("{plugins_translation_file_name} {file_already_parsed_msg}");
+        return Ok(());
+    }
+
+    let content: String = read_file(plugins_file_path)?;
+    let plugins_obj_vec: Vec<Object> = parse_json(plugins_file_path, strip_plugins_prefix(&content))?;
+
+    let mut plugins_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+    let mut plugins_translation_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+    if processing_mode == ProcessingMode::Append {
+        match read_translation_map(output_path, "plugins", format)? {
+            Some(translation_map) => plugins_translation_map = translation_map,
+            None => {
+                println!("{file_is_not_parsed_msg}");
+                processing_mode = &ProcessingMode::Default;
+            }
+        }
+    }
+
+    for obj in plugins_obj_vec {
+        let name: &str = obj["name"].as_str().unwrap_or_default();
+
+        // Some games have a lot of plugins with untranslatable strings, so their profiles list the plugins with the
+        // needed text
+        if game_profile
+            .as_ref()
+            .is_some_and(|game_profile: &GameProfile| !game_profile.parses_plugin(name))
+        {
+            continue;
+        }
+
+        let parameters: &Object = match obj["parameters"].as_object() {
+            Some(parameters) => parameters,
+            None => continue,
+        };
+
+        for (key, value) in parameters.iter() {
+            let mut parsed_strings: Vec<String> = Vec::new();
+            parse_plugin_value(value, &mut parsed_strings);
+            parsed_strings.retain(|parsed: &String| !rules.skips_string(Section::Plugins, parsed));
+
+            for mut parsed in parsed_strings {
+                if romanize {
+                    parsed = romanize_string(parsed);
+                }
+
+                parsed = parsed.replace('\n', r"\#");
+
+                insert_line(
+                    parsed,
+                    format!("plugins.js:{name}:{key}"),
+                    processing_mode,
+                    &mut plugins_lines,
+                    &mut plugins_translation_map,
+                );
+            }
+        }
+    }
+
+    write_lines(
+        output_path,
+        "plugins",
+        format,
+        contextual_keys,
+        locations,
+        processing_mode,
+        plugins_lines,
+        plugins_translation_map,
+        None,
+    )?;
+
+    if logging {
+        println!("{file_parsed_msg} plugins.js.");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env::temp_dir,
+        fs::{create_dir_all, remove_dir_all},
+        path::PathBuf,
+    };
+
+    #[test]
+    fn speaker_comment_is_attached_only_to_its_dialogue_lines() {
+        let list: Array = sonic_rs::from_str(
+            r#"[
+                {"code": 101, "indent": 0, "parameters": ["Actor1", 0, 0, 2, "Guard"]},
+                {"code": 401, "indent": 0, "parameters": ["Halt!"]},
+                {"code": 105, "indent": 0, "parameters": [2, false]},
+                {"code": 405, "indent": 0, "parameters": ["Scrolling line"]},
+                {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2, ""]},
+                {"code": 401, "indent": 0, "parameters": ["Nameless line"]},
+                {"code": 0, "indent": 0, "parameters": []}
+            ]"#,
+        )
+        .unwrap();
+
+        let mut lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut speakers_lines: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut speakers_map: IndexMap<String, String, BuildHasherDefault<Xxh3>> = IndexMap::default();
+        let mut comments: IndexMap<String, Vec<String>, BuildHasherDefault<Xxh3>> = IndexMap::default();
+
+        parse_list(
+            &list,
+            &[101, 401, 105, 405],
+            "Map001.json:1:0",
+            false,
+            false,
+            Section::Maps,
+            &None,
+            &Rules::default(),
+            &ProcessingMode::Default,
+            &mut lines,
+            &mut map,
+            &ProcessingMode::Default,
+            &mut speakers_lines,
+            &mut speakers_map,
+            Some(&mut comments),
+        );
+
+        assert_eq!(
+            lines.keys().collect::<Vec<&String>>(),
+            ["Halt!", "Scrolling line", "Nameless line"]
+        );
+        assert_eq!(
+            comments.into_iter().collect::<Vec<(String, Vec<String>)>>(),
+            [("Halt!".to_string(), vec!["Guard:".to_string()])]
+        );
+    }
+
+    #[test]
+    fn append_keeps_translation_after_missing_translation_file() {
+        let root: PathBuf = temp_dir().join(format!("rvpacker-read-other-{}", std::process::id()));
+        let data_path: PathBuf = root.join("data");
+        let output_path: PathBuf = root.join("translation");
+        create_dir_all(&data_path).unwrap();
+        create_dir_all(&output_path).unwrap();
+
+        // Actors.json is read before Items.json, and has no translation yet
+        write_file(
+            &data_path.join("Actors.json"),
+            r#"[null, {"id": 1, "name": "Harold", "nickname": "", "profile": "", "note": ""}]"#,
+        )
+        .unwrap();
+        write_file(
+            &data_path.join("Items.json"),
+            r#"[null, {"id": 1, "name": "Potion", "description": "", "note": ""}, {"id": 2, "name": "Ether", "description": "", "note": ""}]"#,
+        )
+        .unwrap();
+        write_file(&output_path.join("items.txt"), "Potion").unwrap();
+        write_file(&output_path.join("items_trans.txt"), "Зелье").unwrap();
+
+        let errors: Vec<Error> = read_other(
+            &data_path,
+            &output_path,
+            false,
+            false,
+            "",
+            "",
+            "",
+            &None,
+            &Rules::default(),
+            &ProcessingMode::Append,
+            Format::Txt,
+            false,
+            false,
+            false,
+            &[],
+            false,
+        );
+        assert!(errors.is_empty());
+
+        let items: String = read_file(&output_path.join("items.txt")).unwrap();
+        let items_translation: String = read_file(&output_path.join("items_trans.txt")).unwrap();
+        let actors: String = read_file(&output_path.join("actors.txt")).unwrap();
+        remove_dir_all(&root).unwrap();
+
+        assert_eq!(items, "Potion\nEther");
+        assert_eq!(items_translation, "Зелье\n");
+        assert_eq!(actors, "Harold");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{
+    error::{parse_json, read_file, Error},
+    profile::{objects, parse_code, parse_variable, strings, GameProfile},
+    project::Section,
+    Code, Variable,
+};
+use regex::Regex;
+use sonic_rs::{JsonValueTrait, Value};
+use std::{ops::Range, path::Path};
+
+/// Name of the rules file of the translation directory, that is used, if no rules file is specified.
+pub const RULES_FILE_NAME: &str = "rules.json";
+
+/// Rule, that includes or excludes strings of the sections. Rules with codes apply only to the text of event commands of
+/// these kinds, and rules with variables apply only to these variables of the database files. Rules without both of them
+/// apply to every string of the sections.
+struct Rule {
+    sections: Vec<Section>,
+    codes: Vec<Code>,
+    variables: Vec<Variable>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+}
+
+fn parse_section(name: &str) -> Result<Section, String> {
+    match name {
+        "maps" => Ok(Section::Maps),
+        "other" => Ok(Section::Other),
+        "system" => Ok(Section::System),
+        "plugins" => Ok(Section::Plugins),
+        _ => Err(format!("unknown section {name}")),
+    }
+}
+
+fn regexes(value: Option<&Value>) -> Result<Vec<Regex>, String> {
+    strings(value)?
+        .iter()
+        .map(|regex: &String| Regex::new(regex).map_err(|error: regex::Error| error.to_string()))
+        .collect()
+}
+
+impl Rule {
+    fn parse(value: &Value) -> Result<Self, String> {
+        Ok(Rule {
+            sections: strings(value.get("sections"))?
+                .iter()
+                .map(|section: &String| parse_section(section))
+                .collect::<Result<_, _>>()?,
+            codes: strings(value.get("codes"))?
+                .iter()
+                .map(|code: &String| parse_code(code))
+                .collect::<Result<_, _>>()?,
+            variables: strings(value.get("variables"))?
+                .iter()
+                .map(|variable: &String| parse_variable(variable))
+                .collect::<Result<_, _>>()?,
+            include: regexes(value.get("include"))?,
+            exclude: regexes(value.get("exclude"))?,
+        })
+    }
+
+    fn applies_to(&self, section: Section) -> bool {
+        self.sections.is_empty() || self.sections.contains(&section)
+    }
+
+    /// Returns whether the string matches one of the exclude regexes, or the rule has include regexes and the string
+    /// doesn't match any of them.
+    fn skips(&self, string: &str) -> bool {
+        self.exclude.iter().any(|regex: &Regex| regex.is_match(string))
+            || (!self.include.is_empty() && !self.include.iter().any(|regex: &Regex| regex.is_match(string)))
+    }
+}
+
+/// Tags of notes of the files, which names start with one of the prefixes, or of every file, if there's no prefixes.
+/// Only text inside the tags is parsed from such notes: inner text of paired tags, like `<Help Description>` and
+/// `</Help Description>`, and the value of single tags, like `<Menu Category: Items>`. They're declared both by rules and
+/// by game profiles.
+pub(crate) struct NoteTags {
+    files: Vec<String>,
+    regexes: Vec<Regex>,
+}
+
+impl NoteTags {
+    pub(crate) fn parse(value: &Value) -> Result<Self, String> {
+        let regexes: Vec<Regex> = strings(value.get("tags"))?
+            .iter()
+            .map(|tag: &String| {
+                let tag: String = regex::escape(tag);
+                Regex::new(&format!(r"(?is)<{tag}>\s*(.*?)\s*</{tag}>|<{tag}:\s*([^>]*?)\s*>"))
+                    .map_err(|error: regex::Error| error.to_string())
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(NoteTags {
+            files: strings(value.get("files"))?,
+            regexes,
+        })
+    }
+
+    fn applies_to(&self, filename: &str) -> bool {
+        self.files.is_empty() || self.files.iter().any(|prefix: &String| filename.starts_with(prefix))
+    }
+}
+
+/// User-defined rules, that decide which strings are translatable. They're applied on top of the built-in filtering
+/// and the game profile, both when reading and when writing.
+#[derive(Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+    note_tags: Vec<NoteTags>,
+}
+
+impl Rules {
+    /// Parses the rules from their JSON content. Returns the description of the problem, if the rules are invalid.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let rules: Value = sonic_rs::from_str(content).map_err(|error: sonic_rs::Error| error.to_string())?;
+
+        Ok(Rules {
+            rules: objects(rules.get("rules"), Rule::parse)?,
+            note_tags: objects(rules.get("note_tags"), NoteTags::parse)?,
+        })
+    }
+
+    /// Loads the rules from the JSON file.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content: String = read_file(path)?;
+
+        // Syntax errors are reported with the position in the file
+        parse_json::<Value>(path, &content)?;
+        Rules::parse(&content).map_err(|message: String| Error::InvalidRules(path.to_path_buf(), message))
+    }
+
+    /// Loads the rules from the rules file, if it's specified, or from the rules file of the translation directory, if
+    /// it exists. Otherwise, returns empty rules, that don't skip anything.
+    /// # Parameters
+    /// * `rules_file_path` - path to the rules file, that is used instead of the rules file of the translation directory
+    /// * `translation_path` - path to the translation directory
+    pub fn find(rules_file_path: Option<&Path>, translation_path: &Path) -> Result<Self, Error> {
+        match rules_file_path {
+            Some(rules_file_path) => Rules::load(rules_file_path),
+            None if translation_path.join(RULES_FILE_NAME).exists() => {
+                Rules::load(&translation_path.join(RULES_FILE_NAME))
+            }
+            None => Ok(Rules::default()),
+        }
+    }
+
+    /// Returns whether the text of the event command with the code isn't parsed.
+    pub fn skips_parameter(&self, section: Section, code: Code, parameter: &str) -> bool {
+        self.rules.iter().any(|rule: &Rule| {
+            rule.applies_to(section)
+                && rule.variables.is_empty()
+                && (rule.codes.is_empty() || rule.codes.contains(&code))
+                && rule.skips(parameter)
+        })
+    }
+
+    /// Returns whether the variable of the object of the database file isn't parsed.
+    pub fn skips_variable(&self, variable_type: &Variable, variable_text: &str) -> bool {
+        self.rules.iter().any(|rule: &Rule| {
+            rule.applies_to(Section::Other)
+                && rule.codes.is_empty()
+                && (rule.variables.is_empty() || rule.variables.contains(variable_type))
+                && rule.skips(variable_text)
+        })
+    }
+
+    /// Returns whether the string of System.json or of plugins.js isn't parsed.
+    pub fn skips_string(&self, section: Section, string: &str) -> bool {
+        self.rules.iter().any(|rule: &Rule| {
+            rule.applies_to(section) && rule.codes.is_empty() && rule.variables.is_empty() && rule.skips(string)
+        })
+    }
+
+    // Note tags of the rules and of the game profile apply together
+    fn all_note_tags<'a>(&'a self, game_profile: &'a Option<GameProfile>) -> impl Iterator<Item = &'a NoteTags> {
+        self.note_tags.iter().chain(
+            game_profile
+                .iter()
+                .flat_map(|game_profile: &GameProfile| game_profile.note_tags.iter()),
+        )
+    }
+
+    /// Returns whether only text inside the tags is parsed from notes of the file.
+    pub fn has_note_tags(&self, filename: &str, game_profile: &Option<GameProfile>) -> bool {
+        self.all_note_tags(game_profile)
+            .any(|note_tags: &NoteTags| note_tags.applies_to(filename))
+    }
+
+    /// Returns ranges of the non-empty text inside the tags of the note, sorted by their position. Tags, that are
+    /// nested in the already found tags, are ignored.
+    pub fn note_tag_ranges(&self, filename: &str, note: &str, game_profile: &Option<GameProfile>) -> Vec<Range<usize>> {
+        let mut ranges: Vec<Range<usize>> = self
+            .all_note_tags(game_profile)
+            .filter(|note_tags: &&NoteTags| note_tags.applies_to(filename))
+            .flat_map(|note_tags: &NoteTags| note_tags.regexes.iter())
+            .flat_map(|regex: &Regex| regex.captures_iter(note))
+            .filter_map(|captures: regex::Captures| captures.get(1).or_else(|| captures.get(2)))
+            .filter(|text: &regex::Match| !text.is_empty())
+            .map(|text: regex::Match| text.range())
+            .collect();
+
+        ranges.sort_by_key(|range: &Range<usize>| range.start);
+        ranges.dedup_by(|range: &mut Range<usize>, previous: &mut Range<usize>| range.start < previous.end);
+        ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_are_scoped_by_sections_codes_and_variables() {
+        let rules: Rules = Rules::parse(
+            r#"{
+                "rules": [
+                    { "exclude": ["\\.png$"] },
+                    { "sections": ["maps"], "codes": ["choice"], "exclude": ["^switch_"] },
+                    { "sections": ["other"], "variables": ["name"], "include": ["^[A-Z]"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(rules.skips_string(Section::Plugins, "Actor1.png"));
+        assert!(!rules.skips_string(Section::Plugins, "Hello"));
+
+        assert!(rules.skips_parameter(Section::Maps, Code::Choice, "switch_on"));
+        assert!(!rules.skips_parameter(Section::Other, Code::Choice, "switch_on"));
+        assert!(!rules.skips_parameter(Section::Maps, Code::Dialogue, "switch_on"));
+        assert!(!rules.skips_string(Section::Maps, "switch_on"));
+
+        assert!(rules.skips_variable(&Variable::Name, "lowercase"));
+        assert!(!rules.skips_variable(&Variable::Name, "Harold"));
+        assert!(!rules.skips_variable(&Variable::Description, "lowercase"));
+    }
+
+    #[test]
+    fn invalid_rules_are_reported() {
+        assert!(Rules::parse(r#"{ "rules": [{ "sections": ["unknown"] }] }"#).is_err());
+        assert!(Rules::parse(r#"{ "rules": [{ "codes": ["monologue"] }] }"#).is_err());
+        assert!(Rules::parse(r#"{ "rules": [{ "exclude": ["("] }] }"#).is_err());
+        assert!(Rules::parse(r#"{ "rules": 5 }"#).is_err());
+        assert!(Rules::parse("{").is_err());
+    }
+
+    #[test]
+    fn note_tag_ranges_are_sorted_and_deduplicated() {
+        let rules: Rules = Rules::parse(
+            r#"{
+                "note_tags": [
+                    { "files": ["Items"], "tags": ["Help", "Category"] },
+                    { "tags": ["Help"] }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let note: &str = "<Category: Healing>\n<Help>\n  A potion. <Category: Nested>\n</Help>\n<Help></Help>";
+        let texts: Vec<&str> = rules
+            .note_tag_ranges("Items.json", note, &None)
+            .into_iter()
+            .map(|range: Range<usize>| &note[range])
+            .collect();
+
+        // Help tag is found by both entries, and the nested tag is inside of it, so they're found only once
+        assert_eq!(texts, ["Healing", "A potion. <Category: Nested>"]);
+
+        assert!(rules.has_note_tags("Weapons.json", &None));
+        assert_eq!(
+            rules.note_tag_ranges("Weapons.json", "<Category: Sword>", &None),
+            Vec::<Range<usize>>::new()
+        );
+    }
+
+    #[test]
+    fn note_tags_of_game_profile_apply_with_rules() {
+        let rules: Rules = Rules::default();
+        let game_profile: Option<GameProfile> = GameProfile::builtin_by_id("termina");
+        let note: &str = "<Menu Category: Food>";
+
+        assert!(!rules.has_note_tags("Items.json", &None));
+        assert!(rules.has_note_tags("Items.json", &game_profile));
+        assert!(!rules.has_note_tags("Weapons.json", &game_profile));
+        assert_eq!(
+            rules
+                .note_tag_ranges("Items.json", note, &game_profile)
+                .into_iter()
+                .map(|range: Range<usize>| &note[range])
+                .collect::<Vec<&str>>(),
+            ["Food"]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    error::{read_file, Error},
+    po::{parse_po, PoEntry},
+    section_names, Format, CONTEXT_SEPARATOR, SECTION_DIRS,
+};
+use sonic_rs::to_string;
+use std::path::Path;
+
+/// Translation progress of a translation file, or of several of them.
+#[derive(Default)]
+struct Stats {
+    lines: usize,
+    translated: usize,
+    identical: usize,
+    empty: usize,
+    characters: usize,
+    words: usize,
+}
+
+impl Stats {
+    fn add_line(&mut self, original: &str, translated: &str) {
+        let original: String = original.replace(r"\#", "\n");
+
+        self.lines += 1;
+        self.characters += original.chars().filter(|char: &char| *char != '\n').count();
+        self.words += original.split_whitespace().count();
+
+        if translated.trim().is_empty() {
+            self.empty += 1;
+        } else if translated.replace(r"\#", "\n") == original {
+            self.identical += 1;
+        } else {
+            self.translated += 1;
+        }
+    }
+
+    fn add(&mut self, other: &Stats) {
+        self.lines += other.lines;
+        self.translated += other.translated;
+        self.identical += other.identical;
+        self.empty += other.empty;
+        self.characters += other.characters;
+        self.words += other.words;
+    }
+
+    fn progress(&self) -> f64 {
+        if self.lines == 0 {
+            100f64
+        } else {
+            self.translated as f64 / self.lines as f64 * 100f64
+        }
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            r#""lines":{},"translated":{},"identical":{},"empty":{},"characters":{},"words":{},"progress":{:.2}"#,
+            self.lines,
+            self.translated,
+            self.identical,
+            self.empty,
+            self.characters,
+            self.words,
+            self.progress()
+        )
+    }
+
+    fn to_row(&self, name: &str, name_width: usize) -> String {
+        format!(
+            "{name:<name_width$} {:>8} {:>10} {:>10} {:>8} {:>12} {:>10} {:>9.1}%",
+            self.lines,
+            self.translated,
+            self.identical,
+            self.empty,
+            self.characters,
+            self.words,
+            self.progress()
+        )
+    }
+}
+
+/// Counts translation progress of the section. Location-specific entries are skipped, since their empty translation
+/// means that the translation of the line is used.
+fn section_stats(path: &Path, name: &str, format: Format) -> Result<Stats, Error> {
+    let mut stats: Stats = Stats::default();
+
+    match format {
+        Format::Txt => {
+            let original_content: String = read_file(&path.join(format!("{name}.txt")))?;
+            let translated_content: String = read_file(&path.join(format!("{name}_trans.txt")))?;
+            let mut translated_lines = translated_content.split('\n');
+
+            for original in original_content.split('\n') {
+                let translated: &str = translated_lines.next().unwrap_or_default();
+
+                if !original.contains(CONTEXT_SEPARATOR) {
+                    stats.add_line(original, translated);
+                }
+            }
+        }
+        Format::Po => {
+            for entry in parse_po(&read_file(&path.join(format!("{name}.po")))?)
+                .into_iter()
+                .filter(|entry: &PoEntry| entry.context.is_none())
+            {
+                stats.add_line(&entry.original, &entry.translated);
+            }
+        }
+    }
+
+    Ok(stats)
+}
+
+/// Prints translation progress of every translation file of maps, other and plugins folders, and the total progress:
+/// numbers of translated lines, lines with translation identical to the original and empty lines, and numbers of
+/// characters and words of the original text. Returns the error of the translation file, that couldn't be read.
+/// # Parameters
+/// * `translation_path` - path to the translation directory
+/// * `format` - format of translation files
+/// * `json` - whether to print the report as JSON instead of a table
+/// * `columns` - localized column names of the table: file, lines, translated, identical, empty, characters, words and
+///   progress
+/// * `total` - localized name of the total row of the table
+pub fn stats(
+    translation_path: &Path,
+    format: Format,
+    json: bool,
+    columns: &[&str; 8],
+    total: &str,
+) -> Result<(), Error> {
+    let extension: &str = match format {
+        Format::Txt => "txt",
+        Format::Po => "po",
+    };
+
+    let mut files_stats: Vec<(String, Stats)> = Vec::new();
+
+    for dir in SECTION_DIRS {
+        let path: &Path = &translation_path.join(dir);
+
+        if !path.exists() {
+            continue;
+        }
+
+        for name in section_names(path, format)? {
+            files_stats.push((format!("{dir}/{name}.{extension}"), section_stats(path, &name, format)?));
+        }
+    }
+
+    let mut total_stats: Stats = Stats::default();
+
+    for (_, file_stats) in &files_stats {
+        total_stats.add(file_stats);
+    }
+
+    if json {
+        let files: Vec<String> = files_stats
+            .iter()
+            .map(|(file, file_stats)| format!(r#"{{"file":{},{}}}"#, to_string(file).unwrap(), file_stats.to_json()))
+            .collect();
+
+        println!(
+            r#"{{"files":[{}],"total":{{{}}}}}"#,
+            files.join(","),
+            total_stats.to_json()
+        );
+        return Ok(());
+    }
+
+    let name_width: usize = files_stats
+        .iter()
+        .map(|(file, _)| file.chars().count())
+        .chain([columns[0].chars().count(), total.chars().count()])
+        .max()
+        .unwrap();
+
+    println!(
+        "{:<name_width$} {:>8} {:>10} {:>10} {:>8} {:>12} {:>10} {:>10}",
+        columns[0], columns[1], columns[2], columns[3], columns[4], columns[5], columns[6], columns[7]
+    );
+
+    for (file, file_stats) in &files_stats {
+        println!("{}", file_stats.to_row(file, name_width));
+    }
+
+    println!("{}", total_stats.to_row(total, name_width));
+    Ok(())
+}
@@ -0,0 +1,395 @@
+#![allow(clippy::too_many_arguments)]
+use crate::{
+    error::{create_dir, parse_json, read_directory, read_file, remove_dir, write_file, Error},
+    profile::GameProfile,
+    read::{read_map, read_other, read_plugins, read_system},
+    rules::Rules,
+    strip_plugins_prefix,
+    write::{write_maps, write_other, write_plugins, write_system},
+    Format, ProcessingMode,
+};
+use sonic_rs::{to_string, JsonContainerTrait, Value};
+use std::{
+    env::temp_dir,
+    fs::{remove_dir_all, DirEntry},
+    mem::take,
+    path::{Path, PathBuf},
+    process::id,
+};
+
+/// Temporary directory, that is removed when dropped, so it doesn't outlive the verification on any exit path.
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Removes the directory, returning the error, that is ignored when the directory is removed on drop.
+    fn remove(mut self) -> Result<(), Error> {
+        remove_dir(&take(&mut self.path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if !self.path.as_os_str().is_empty() {
+            let _ = remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Fills translation of every .txt file in the directory with its original text, so writing must reproduce the
+/// original files.
+fn copy_originals_as_translation(path: &Path) -> Result<(), Error> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    for entry in read_directory(path)?.flatten() {
+        let Ok(filename) = entry.file_name().into_string() else {
+            continue;
+        };
+
+        if let Some(name) = filename.strip_suffix(".txt") {
+            if !name.ends_with("_trans") {
+                write_file(&path.join(format!("{name}_trans.txt")), read_file(&entry.path())?)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Structurally compares the written value with the original one, pushing a description of every changed field to
+/// differences.
+fn diff_values(path: &str, original: &Value, written: &Value, differences: &mut Vec<String>) {
+    if let (Some(original_object), Some(written_object)) = (original.as_object(), written.as_object()) {
+        for (key, original_value) in original_object.iter() {
+            let key_path: String = format!("{path}.{key}");
+
+            match written_object.get(&key) {
+                Some(written_value) => diff_values(&key_path, original_value, written_value, differences),
+                None => differences.push(format!("{key_path}: {} -> ", to_string(original_value).unwrap())),
+            }
+        }
+
+        for (key, written_value) in written_object.iter() {
+            if original_object.get(&key).is_none() {
+                differences.push(format!("{path}.{key}:  -> {}", to_string(written_value).unwrap()));
+            }
+        }
+    } else if let (Some(original_array), Some(written_array)) = (original.as_array(), written.as_array()) {
+        if original_array.len() != written_array.len() {
+            differences.push(format!(
+                "{path}: {} items -> {} items",
+                original_array.len(),
+                written_array.len()
+            ));
+        }
+
+        for (i, (original_value, written_value)) in original_array.iter().zip(written_array.iter()).enumerate() {
+            diff_values(&format!("{path}[{i}]"), original_value, written_value, differences);
+        }
+    } else if original != written {
+        differences.push(format!(
+            "{path}: {} -> {}",
+            to_string(original).unwrap(),
+            to_string(written).unwrap()
+        ));
+    }
+}
+
+/// Compares the written file with the original one, returning descriptions of all changed fields. JSON content of the
+/// files is taken from their content with the function.
+fn diff_files(
+    original_file_path: &Path,
+    written_file_path: &Path,
+    json_content: fn(&str) -> &str,
+) -> Result<Vec<String>, Error> {
+    let original: Value = parse_json(original_file_path, json_content(&read_file(original_file_path)?))?;
+    let written: Value = parse_json(written_file_path, json_content(&read_file(written_file_path)?))?;
+
+    let mut differences: Vec<String> = Vec::new();
+    diff_values("", &original, &written, &mut differences);
+    Ok(differences)
+}
+
+/// Verifies that reading and writing of the game is lossless: reads the game files into a temporary directory, uses
+/// the original text as translation, writes the files back and structurally compares every written file with its
+/// original. Prints every field, that changed, and returns the number of such fields along with errors of the files,
+/// that couldn't be read or written.
+/// # Parameters
+/// * `original_path` - path to the directory, that contains original .json game files
+/// * `plugins_file_path` - path to the plugins.js file, if it exists
+/// * `romanize` - whether to romanize text
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `codes` - codes of the event commands, which text is extracted
+/// * `disable_processing` - whether to skip processing of maps, other, system and plugins files
+/// * `logging` - whether to log or not
+/// * `file_verified_msg` - message to log when file is verified
+pub fn verify(
+    original_path: &Path,
+    plugins_file_path: Option<&Path>,
+    romanize: bool,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    codes: &[u64],
+    disable_processing: (bool, bool, bool, bool),
+    logging: bool,
+    file_verified_msg: &str,
+) -> (usize, Vec<Error>) {
+    let (disable_maps_processing, disable_other_processing, disable_system_processing, disable_plugins_processing) =
+        disable_processing;
+
+    let temp_dir: TempDir = TempDir {
+        path: temp_dir().join(format!("rvpacker-json-txt-verify-{}", id())),
+    };
+    let temp_path: &Path = &temp_dir.path;
+    let maps_path: PathBuf = temp_path.join("translation/maps");
+    let other_path: PathBuf = temp_path.join("translation/other");
+    let plugins_path: PathBuf = temp_path.join("translation/plugins");
+    let output_path: PathBuf = temp_path.join("output/data");
+    let plugins_output_path: PathBuf = temp_path.join("output/js");
+
+    for path in [
+        &maps_path,
+        &other_path,
+        &plugins_path,
+        &output_path,
+        &plugins_output_path,
+    ] {
+        if let Err(error) = create_dir(path) {
+            return (0, vec![error]);
+        }
+    }
+
+    let mut errors: Vec<Error> = Vec::new();
+    let system_file_path: PathBuf = original_path.join("System.json");
+    let plugins_file_path: Option<&Path> = plugins_file_path.filter(|_| !disable_plugins_processing);
+
+    // Translation is read in its simplest form, since every other option only changes the layout of translation files
+    if !disable_maps_processing {
+        errors.extend(read_map(
+            original_path,
+            &maps_path,
+            romanize,
+            false,
+            "",
+            "",
+            "",
+            game_profile,
+            rules,
+            &ProcessingMode::Force,
+            Format::Txt,
+            false,
+            false,
+            false,
+            false,
+            false,
+            codes,
+            false,
+        ));
+    }
+
+    if !disable_other_processing {
+        errors.extend(read_other(
+            original_path,
+            &other_path,
+            romanize,
+            false,
+            "",
+            "",
+            "",
+            game_profile,
+            rules,
+            &ProcessingMode::Force,
+            Format::Txt,
+            false,
+            false,
+            false,
+            codes,
+            false,
+        ));
+    }
+
+    if !disable_system_processing {
+        errors.extend(
+            read_system(
+                &system_file_path,
+                &other_path,
+                romanize,
+                false,
+                "",
+                "",
+                "",
+                rules,
+                &ProcessingMode::Force,
+                Format::Txt,
+                false,
+                false,
+            )
+            .err(),
+        );
+    }
+
+    if let Some(plugins_file_path) = plugins_file_path {
+        errors.extend(
+            read_plugins(
+                plugins_file_path,
+                &plugins_path,
+                romanize,
+                false,
+                "",
+                "",
+                "",
+                game_profile,
+                rules,
+                &ProcessingMode::Force,
+                Format::Txt,
+                false,
+                false,
+            )
+            .err(),
+        );
+    }
+
+    for path in [&maps_path, &other_path, &plugins_path] {
+        errors.extend(copy_originals_as_translation(path).err());
+    }
+
+    if !disable_maps_processing {
+        errors.extend(write_maps(
+            &maps_path,
+            original_path,
+            &output_path,
+            romanize,
+            0,
+            false,
+            "",
+            game_profile,
+            rules,
+            Format::Txt,
+            codes,
+            false,
+            "",
+            None,
+            0,
+        ));
+    }
+
+    if !disable_other_processing {
+        errors.extend(write_other(
+            &other_path,
+            original_path,
+            &output_path,
+            romanize,
+            0,
+            false,
+            "",
+            game_profile,
+            rules,
+            Format::Txt,
+            codes,
+            false,
+            "",
+            None,
+            0,
+        ));
+    }
+
+    if !disable_system_processing {
+        errors.extend(
+            write_system(
+                &system_file_path,
+                &other_path,
+                &output_path,
+                romanize,
+                0,
+                false,
+                "",
+                rules,
+                Format::Txt,
+            )
+            .err(),
+        );
+    }
+
+    let mut written_files: Vec<(String, PathBuf)> = match read_directory(&output_path) {
+        Ok(entries) => entries
+            .flatten()
+            .filter_map(|entry: DirEntry| Some((entry.file_name().into_string().ok()?, entry.path())))
+            .collect(),
+        Err(error) => {
+            errors.push(error);
+            Vec::new()
+        }
+    };
+    written_files.sort();
+
+    let mut differences_count: usize = 0;
+
+    for (filename, written_file_path) in written_files {
+        let differences: Vec<String> =
+            match diff_files(&original_path.join(&filename), &written_file_path, |content: &str| {
+                content
+            }) {
+                Ok(differences) => differences,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+        for difference in &differences {
+            println!("{filename}{difference}");
+        }
+
+        differences_count += differences.len();
+
+        if logging {
+            println!("{file_verified_msg} {filename}");
+        }
+    }
+
+    if let Some(plugins_file_path) = plugins_file_path {
+        if plugins_path.join("plugins_trans.txt").exists() {
+            errors.extend(
+                write_plugins(
+                    plugins_file_path,
+                    &plugins_path,
+                    &plugins_output_path,
+                    romanize,
+                    0,
+                    false,
+                    "",
+                    game_profile,
+                    rules,
+                    Format::Txt,
+                )
+                .err(),
+            );
+
+            match diff_files(
+                plugins_file_path,
+                &plugins_output_path.join("plugins.js"),
+                strip_plugins_prefix,
+            ) {
+                Ok(differences) => {
+                    for difference in &differences {
+                        println!("plugins.js{difference}");
+                    }
+
+                    differences_count += differences.len();
+
+                    if logging {
+                        println!("{file_verified_msg} plugins.js");
+                    }
+                }
+                Err(error) => errors.push(error),
+            }
+        }
+    }
+
+    errors.extend(temp_dir.remove().err());
+    (differences_count, errors)
+}
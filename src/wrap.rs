@@ -0,0 +1,140 @@
+use crate::placeholders::ESCAPE_CODE_RE;
+
+/// Returns the width of the symbol in characters. CJK and other full-width symbols take two characters.
+fn char_width(char: char) -> usize {
+    match char as u32 {
+        0x1100..=0x115F
+        | 0x2E80..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6 => 2,
+        _ => 1,
+    }
+}
+
+/// Splits the word into units, that can't be split further: escape codes and single symbols, with their widths.
+/// Escape codes aren't displayed, except for icons, that take about two characters.
+fn units<'a>(word: &'a str) -> Vec<(&'a str, usize)> {
+    let mut units: Vec<(&'a str, usize)> = Vec::new();
+    let mut last_end: usize = 0;
+
+    let push_chars = |units: &mut Vec<(&'a str, usize)>, start: usize, end: usize| {
+        for (i, char) in word[start..end].char_indices() {
+            units.push((&word[start + i..start + i + char.len_utf8()], char_width(char)));
+        }
+    };
+
+    for code in ESCAPE_CODE_RE.find_iter(word) {
+        push_chars(&mut units, last_end, code.start());
+
+        let width: usize = if code.as_str()[1..].to_uppercase().starts_with("I[") {
+            2
+        } else {
+            0
+        };
+
+        units.push((code.as_str(), width));
+        last_end = code.end();
+    }
+
+    push_chars(&mut units, last_end, word.len());
+    units
+}
+
+/// Returns the displayed width of the text in characters.
+pub fn display_width(text: &str) -> usize {
+    units(text).into_iter().map(|(_, width)| width).sum()
+}
+
+/// Rewraps the text to lines, that are not wider than width, moving words to the next line. Line breaks of the text are
+/// discarded, since they were made for the original text. Words wider than a line, like the text of languages without
+/// spaces, are split between the symbols.
+/// # Parameters
+/// * `text` - text to wrap, which lines are separated by `\n`
+/// * `width` - max width of the line in characters
+pub fn wrap_text(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut line: String = String::new();
+    let mut line_width: usize = 0;
+
+    for word in text.split_whitespace() {
+        let word_width: usize = display_width(word);
+        let space_width: usize = usize::from(!line.is_empty());
+
+        if line_width + space_width + word_width <= width {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            line.push_str(word);
+            line_width += space_width + word_width;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+
+        if word_width <= width {
+            line.push_str(word);
+            line_width = word_width;
+            continue;
+        }
+
+        for (unit, unit_width) in units(word) {
+            if line_width + unit_width > width && !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+
+            line.push_str(unit);
+            line_width += unit_width;
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_counts_full_width_symbols_and_icons() {
+        assert_eq!(display_width("Hello"), 5);
+        assert_eq!(display_width("こんにちは"), 10);
+        assert_eq!(display_width("안녕"), 4);
+        assert_eq!(display_width(r"\C[2]Hi\C[0]"), 2);
+        assert_eq!(display_width(r"\I[64]Potion"), 8);
+        assert_eq!(display_width(r"\i[64]"), 2);
+    }
+
+    #[test]
+    fn words_are_moved_to_the_next_line() {
+        assert_eq!(
+            wrap_text("The quick brown fox\njumps over", 10),
+            ["The quick", "brown fox", "jumps over"]
+        );
+        assert_eq!(
+            wrap_text(r"\C[2]Quick\C[0] brown fox", 11),
+            [r"\C[2]Quick\C[0] brown", "fox"]
+        );
+        assert_eq!(wrap_text(r"\I[1]\I[2] ab", 6), [r"\I[1]\I[2]", "ab"]);
+        assert_eq!(wrap_text("", 10), [""]);
+    }
+
+    #[test]
+    fn long_words_are_split_between_symbols() {
+        assert_eq!(wrap_text("abcdefgh", 3), ["abc", "def", "gh"]);
+        assert_eq!(wrap_text("こんにちは世界", 6), ["こんに", "ちは世", "界"]);
+        // Escape codes are never split
+        assert_eq!(wrap_text(r"ab\C[2]cdef", 3), [r"ab\C[2]c", "def"]);
+    }
+}
@@ -1,19 +1,27 @@
 #![allow(clippy::too_many_arguments)]
-use crate::{romanize_string, Code, GameType, Variable};
+use crate::{
+    codes::{
+        get_code_rule, join_sequence_lines, replace_script_string_literal, script_string_literals, written_codes,
+        Extraction,
+    },
+    database_fields, decode_plugin_parameter,
+    error::{invalid_value, parse_json, read_file, read_json, write_file, Error},
+    find_map_sections, is_map_file, is_other_file,
+    placeholders::{protect_codes, restore_codes},
+    po::{parse_po, PoEntry},
+    profile::{GameProfile, NoteContinuation},
+    project::Section,
+    read_game_files, romanize_string,
+    rules::Rules,
+    strip_plugins_prefix,
+    wrap::wrap_text,
+    Code, DatabaseFields, Format, Variable, CONTEXT_SEPARATOR,
+};
 use fastrand::shuffle;
 use rayon::prelude::*;
 use regex::{Captures, Match, Regex};
-use sonic_rs::{
-    from_str, to_string, to_value, Array, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Object, Value,
-};
-use std::{
-    collections::{HashMap, HashSet},
-    ffi::OsString,
-    fs::{read_dir, read_to_string, write, DirEntry},
-    hash::BuildHasherDefault,
-    path::Path,
-    str::from_utf8_unchecked,
-};
+use sonic_rs::{to_string, to_value, Array, JsonContainerTrait, JsonValueMutTrait, JsonValueTrait, Object, Value};
+use std::{collections::HashMap, hash::BuildHasherDefault, path::Path};
 use xxhash_rust::xxh3::Xxh3;
 
 pub fn shuffle_words(string: &str) -> String {
@@ -26,185 +34,305 @@ pub fn shuffle_words(string: &str) -> String {
         .into_owned()
 }
 
-#[allow(clippy::single_match, clippy::match_single_binding, unused_mut)]
-fn get_translated_parameter<'a>(
-    code: Code,
-    mut parameter: &'a str,
-    hashmap: &'a HashMap<String, String, BuildHasherDefault<Xxh3>>,
-    game_type: &Option<GameType>,
-) -> Option<String> {
-    if let Some(game_type) = game_type {
-        match game_type {
-            GameType::Termina => match code {
-                Code::System => {
-                    if !parameter.starts_with("Gab")
-                        && (!parameter.starts_with("choice_text") || parameter.ends_with("????"))
-                    {
-                        return None;
-                    }
+/// Reads original and translated lines of the section from translation files in the specified format. Returns an
+/// error if a file of the section is missing, or if the number of translated lines differs from the number of original
+/// ones, since translation would be applied to the wrong lines.
+fn read_translation_lines(path: &Path, name: &str, format: Format) -> Result<(Vec<String>, Vec<String>), Error> {
+    match format {
+        Format::Txt => {
+            let original_file_path: &Path = &path.join(format!("{name}.txt"));
+            let translated_file_path: &Path = &path.join(format!("{name}_trans.txt"));
+
+            for file_path in [original_file_path, translated_file_path] {
+                if !file_path.exists() {
+                    return Err(Error::MissingTranslationFile(file_path.to_path_buf()));
                 }
-                _ => {}
-            },
-            // custom processing for other games
+            }
+
+            let original_lines: Vec<String> = read_file(original_file_path)?
+                .par_split('\n')
+                .map(str::to_string)
+                .collect();
+            let translated_lines: Vec<String> = read_file(translated_file_path)?
+                .par_split('\n')
+                .map(str::to_string)
+                .collect();
+
+            if original_lines.len() != translated_lines.len() {
+                return Err(Error::LinesCountMismatch {
+                    path: translated_file_path.to_path_buf(),
+                    original: original_lines.len(),
+                    translated: translated_lines.len(),
+                });
+            }
+
+            Ok((original_lines, translated_lines))
+        }
+        Format::Po => {
+            let po_file_path: &Path = &path.join(format!("{name}.po"));
+
+            if !po_file_path.exists() {
+                return Err(Error::MissingTranslationFile(po_file_path.to_path_buf()));
+            }
+
+            // Fuzzy translation needs review, so it's written as if the entry wasn't translated
+            Ok(parse_po(&read_file(po_file_path)?)
+                .into_iter()
+                .map(|entry: PoEntry| {
+                    let key: String = entry.key();
+                    (key, if entry.fuzzy { String::new() } else { entry.translated })
+                })
+                .unzip())
         }
     }
+}
 
-    let translated: Option<String> = hashmap.get(parameter).map(|translated: &String| {
-        let mut result: String = translated.to_owned();
-        result
-    });
+/// Reads translation of the section into a map from original text to translated text, shuffling translation according
+/// to shuffle_level. Returns an empty map if the section isn't translated.
+fn read_translation_map(
+    path: &Path,
+    name: &str,
+    format: Format,
+    shuffle_level: u8,
+) -> Result<HashMap<String, String, BuildHasherDefault<Xxh3>>, Error> {
+    let translation_file_name: String = match format {
+        Format::Txt => format!("{name}_trans.txt"),
+        Format::Po => format!("{name}.po"),
+    };
+
+    if !path.join(translation_file_name).exists() {
+        return Ok(HashMap::default());
+    }
 
-    if let Some(ref translated) = translated {
-        if translated.is_empty() {
-            return None;
+    let (mut original_text_vec, mut translated_text_vec): (Vec<String>, Vec<String>) =
+        read_translation_lines(path, name, format)?;
+
+    for line in original_text_vec.iter_mut().chain(translated_text_vec.iter_mut()) {
+        *line = line.replace(r"\#", "\n").trim().to_string();
+    }
+
+    match shuffle_level {
+        1 => {
+            shuffle(&mut translated_text_vec);
+        }
+        2 => {
+            for translated_text in translated_text_vec.iter_mut() {
+                *translated_text = shuffle_words(translated_text);
+            }
         }
+        _ => {}
     }
 
-    translated
+    Ok(original_text_vec
+        .into_par_iter()
+        .zip(translated_text_vec.into_par_iter())
+        .fold(
+            HashMap::default,
+            |mut map: HashMap<String, String, BuildHasherDefault<Xxh3>>, (key, value): (String, String)| {
+                map.insert(key, value);
+                map
+            },
+        )
+        .reduce(HashMap::default, |mut a, b| {
+            a.extend(b);
+            a
+        }))
 }
 
-#[allow(clippy::single_match, clippy::match_single_binding, unused_mut)]
-fn get_translated_variable(
-    mut variable_text: String,
-    note_text: Option<&str>, // note_text is some only when getting description
+/// Returns translation of the text, preferring the one that was made specifically for the location of the text.
+fn get_translation<'a>(
+    map: &'a HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    location: &str,
+    text: &str,
+) -> Option<&'a String> {
+    match map.get(&format!("{location}{CONTEXT_SEPARATOR}{text}")) {
+        Some(translated) if !translated.is_empty() => Some(translated),
+        _ => map.get(text),
+    }
+}
+
+/// Returns translation of the event command text, if it's not empty.
+fn get_translated_parameter(
+    parameter: &str,
+    location: &str,
+    hashmap: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+) -> Option<String> {
+    get_translation(hashmap, location, parameter)
+        .filter(|translated: &&String| !translated.is_empty())
+        .cloned()
+}
+
+/// Returns translation of the event command text. If the text was read with placeholders, escape codes are restored from
+/// them, and the translation, which placeholders don't match the original, isn't used.
+fn get_translated_text(
+    code: Code,
+    parameter: &str,
+    location: &str,
+    hashmap: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    section: Section,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    placeholders: bool,
+    placeholders_mismatch_msg: &str,
+) -> Option<String> {
+    // Text is skipped by its original form, as when reading, before escape codes are replaced with placeholders
+    if rules.skips_parameter(section, code, parameter)
+        || game_profile
+            .as_ref()
+            .is_some_and(|game_profile: &GameProfile| game_profile.skips_parameter(code, parameter))
+    {
+        return None;
+    }
+
+    if !placeholders {
+        return get_translated_parameter(parameter, location, hashmap);
+    }
+
+    let (protected, codes): (String, Vec<(String, String)>) = protect_codes(parameter);
+    let translated: String = get_translated_parameter(&protected, location, hashmap)?;
+    let restored: Option<String> = restore_codes(&translated, &codes);
+
+    if restored.is_none() {
+        println!("{placeholders_mismatch_msg} {location}");
+    }
+
+    restored
+}
+
+/// Returns the translation of the field of the object of the database file, that isn't handled by the game profile,
+/// like names of troops and notes of learnings of classes.
+fn get_translated_field(
+    variable_str: &str,
     variable_type: Variable,
-    filename: &str,
+    location: &str,
     hashmap: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
-    game_type: &Option<GameType>,
+    romanize: bool,
+    rules: &Rules,
 ) -> Option<String> {
-    let mut remaining_strings: Vec<String> = Vec::new();
-    let mut insert_positions: Vec<bool> = Vec::new();
-
-    if let Some(game_type) = game_type {
-        match game_type {
-            GameType::Termina => match variable_type {
-                Variable::Description => match note_text {
-                    Some(mut note) => {
-                        let mut note_string: String = String::from(note);
-
-                        let mut note_chars: std::str::Chars = note.chars();
-                        let mut is_continuation_of_description: bool = false;
-
-                        if !note.starts_with("flesh puppetry") {
-                            if let Some(first_char) = note_chars.next() {
-                                if let Some(second_char) = note_chars.next() {
-                                    if ((first_char == '\n' && second_char != '\n')
-                                        || (first_char.is_ascii_alphabetic()
-                                            || first_char == '"'
-                                            || note.starts_with("4 sticks")))
-                                        && !['.', '!', '/', '?'].contains(&first_char)
-                                    {
-                                        is_continuation_of_description = true;
-                                    }
-                                }
-                            }
-                        }
+    let mut variable_text: String = variable_str.trim().to_string();
 
-                        if is_continuation_of_description {
-                            if let Some((mut left, _)) = note.trim_start().split_once('\n') {
-                                left = left.trim();
+    if variable_text.is_empty() || rules.skips_variable(&variable_type, &variable_text) {
+        return None;
+    }
 
-                                if left.ends_with(['.', '%', '!', '"']) {
-                                    note_string = "\n".to_string() + left;
-                                }
-                            } else if note.ends_with(['.', '%', '!', '"']) {
-                                note_string = note.to_string();
-                            }
+    if romanize {
+        variable_text = romanize_string(variable_text);
+    }
 
-                            if !note_string.is_empty() {
-                                variable_text = variable_text + &note_string;
-                            }
-                        }
-                    }
-                    None => {}
-                },
-                Variable::Message1 | Variable::Message2 | Variable::Message3 | Variable::Message4 => {
-                    return None;
-                }
-                Variable::Note => {
-                    if filename.starts_with("It") {
-                        for string in [
-                            "<Menu Category: Items>",
-                            "<Menu Category: Food>",
-                            "<Menu Category: Healing>",
-                            "<Menu Category: Body bag>",
-                        ] {
-                            if variable_text.contains(string) {
-                                variable_text = variable_text.replace(string, &hashmap[string]);
-                            }
-                        }
-                    }
+    let variable_text: String = variable_text
+        .split('\n')
+        .map(|line: &str| line.trim())
+        .collect::<Vec<_>>()
+        .join("\n");
 
-                    if !filename.starts_with("Cl") {
-                        let mut variable_text_chars: std::str::Chars = variable_text.chars();
-                        let mut is_continuation_of_description: bool = false;
-
-                        if let Some(first_char) = variable_text_chars.next() {
-                            if let Some(second_char) = variable_text_chars.next() {
-                                if ((first_char == '\n' && second_char != '\n')
-                                    || (first_char.is_ascii_alphabetic()
-                                        || first_char == '"'
-                                        || variable_text.starts_with("4 sticks")))
-                                    && !['.', '!', '/', '?'].contains(&first_char)
-                                {
-                                    is_continuation_of_description = true;
-                                }
-                            }
-                        }
+    get_translation(hashmap, location, &variable_text)
+        .filter(|translated: &&String| !translated.is_empty())
+        .cloned()
+}
 
-                        if is_continuation_of_description {
-                            if let Some((_, right)) = variable_text.trim_start().split_once('\n') {
-                                return Some(right.to_string());
-                            } else {
-                                return Some("".to_string());
-                            }
-                        } else {
-                            return Some(variable_text);
-                        }
-                    }
-                }
-                _ => {}
-            },
-            // custom processing for other games
+/// Replaces text inside the tags of the note with its translation, keeping the rest of the note as is.
+fn translate_note_tags(
+    mut note: String,
+    filename: &str,
+    location: &str,
+    hashmap: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    romanize: bool,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+) -> String {
+    // Tags are replaced from the end, so ranges of the previous ones stay valid. Untranslated text is kept with its
+    // original whitespace
+    for range in rules.note_tag_ranges(filename, &note, game_profile).into_iter().rev() {
+        let text: &str = &note[range.clone()];
+
+        if rules.skips_variable(&Variable::Note, text) {
+            continue;
         }
-    }
 
-    let translated: Option<String> = hashmap.get(&variable_text).map(|translated: &String| {
-        let mut result: String = translated.to_owned();
+        let mut key: String = text.to_string();
 
-        for (string, position) in remaining_strings.into_iter().zip(insert_positions.into_iter()) {
-            match position {
-                true => {
-                    result.push_str(&string);
-                }
-                false => result = string.to_owned() + &result,
-            }
+        if romanize {
+            key = romanize_string(key);
         }
 
-        if matches!(
-            variable_type,
-            Variable::Message1 | Variable::Message2 | Variable::Message3 | Variable::Message4
-        ) {
-            result = " ".to_owned() + &result;
+        key = key
+            .split('\n')
+            .map(|line: &str| line.trim())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if let Some(translated) = get_translation(hashmap, location, &key)
+            .filter(|translated: &&String| !translated.is_empty() && **translated != key)
+        {
+            note.replace_range(range, translated);
         }
+    }
 
-        #[allow(clippy::collapsible_if, clippy::collapsible_match)]
-        if let Some(game_type) = game_type {
-            match game_type {
-                GameType::Termina => {
-                    match variable_type {
-                        Variable::Note => {
-                            if let Some(first_char) = result.chars().next() {
-                                if first_char != '\n' {
-                                    result = "\n".to_owned() + &result
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
-                    if variable_type == Variable::Note {}
+    note
+}
+
+fn get_translated_variable(
+    mut variable_text: String,
+    note_text: Option<&str>, // note_text is some only when getting description
+    variable_type: Variable,
+    filename: &str,
+    location: &str,
+    hashmap: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+) -> Option<String> {
+    if rules.skips_variable(&variable_type, &variable_text) {
+        return None;
+    }
+
+    if let Some(game_profile) = game_profile {
+        match variable_type {
+            Variable::Description => {
+                let note_continuation: Option<&str> = note_text.zip(game_profile.note_continuation(filename)).and_then(
+                    |(note, note_continuation): (&str, &NoteContinuation)| {
+                        note_continuation.description_continuation(note.trim())
+                    },
+                );
+
+                if let Some(note_continuation) = note_continuation {
+                    variable_text = variable_text + "\n" + note_continuation;
                 }
             }
+            Variable::Note => {
+                // Continuation of the description is written to the description, and the rest of the note is kept
+                if let Some(note_continuation) = game_profile.note_continuation(filename) {
+                    return match note_continuation.description_continuation(variable_text.trim()) {
+                        Some(_) => Some(
+                            variable_text
+                                .trim_start()
+                                .split_once('\n')
+                                .map(|(_, right)| right.to_string())
+                                .unwrap_or_default(),
+                        ),
+                        None => Some(variable_text),
+                    };
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Notes with tags are translated only through their tags
+    if variable_type == Variable::Note && rules.has_note_tags(filename, game_profile) {
+        return None;
+    }
+
+    let translated: Option<String> = get_translation(hashmap, location, &variable_text).map(|translated: &String| {
+        let mut result: String = translated.to_owned();
+
+        if variable_type == Variable::Note
+            && game_profile
+                .as_ref()
+                .is_some_and(|game_profile: &GameProfile| game_profile.has_note_continuation())
+            && !result.is_empty()
+            && !result.starts_with('\n')
+        {
+            result = "\n".to_owned() + &result
         }
 
         result
@@ -219,114 +347,343 @@ fn get_translated_variable(
     translated
 }
 
-fn write_list(
+/// Writes the lines of the translation to the commands of the sequence, growing and shrinking the sequence, so every
+/// line is written to its own command. Empty commands before the first and after the last line of the original text
+/// are left as is. Lines, that don't fit into the existing commands, are written to new continuation commands with the
+/// same indent, and commands, that aren't needed anymore, are removed. If max lines are specified, lines, that don't
+/// fit into the message box, start a new message with the same show text header, if the message has it. Returns the
+/// commands to insert after the last filled command of the sequence.
+/// # Parameters
+/// * `list` - list of the event commands
+/// * `item_indices` - indices of the commands of the sequence
+/// * `original_lines` - original text of the commands of the sequence
+/// * `parameter` - parameter of the commands, that contains the text
+/// * `continuation` - code of the commands, that continue the sequence
+/// * `lines` - lines of the translation
+/// * `max_lines` - max number of lines in a message box, if messages are split
+/// * `removed` - indices of the commands to remove from the list
+fn write_sequence(
+    list: &mut Array,
+    item_indices: &[usize],
+    original_lines: &[String],
+    parameter: usize,
+    continuation: u64,
+    lines: &[String],
+    max_lines: Option<usize>,
+    removed: &mut Vec<usize>,
+) -> (usize, Vec<Value>) {
+    let first: usize = original_lines
+        .iter()
+        .position(|line: &String| !line.trim().is_empty())
+        .unwrap_or_default();
+    let last: usize = original_lines
+        .iter()
+        .rposition(|line: &String| !line.trim().is_empty())
+        .unwrap_or_default();
+    let filled_indices: &[usize] = &item_indices[first..=last];
+
+    let header: Option<Value> = max_lines
+        .and_then(|_| item_indices[0].checked_sub(1))
+        .map(|index: usize| &list[index])
+        .filter(|command: &&Value| continuation == 401 && command["code"].as_u64() == Some(101))
+        .cloned();
+
+    // Without a header, the message can't be split, so all lines are left in a single box
+    let boxes: Vec<&[String]> = match (&header, max_lines) {
+        (Some(_), Some(max_lines)) => lines.chunks(max_lines.max(1)).collect(),
+        _ => vec![lines],
+    };
+
+    for (i, &index) in filled_indices.iter().enumerate() {
+        match boxes[0].get(i) {
+            Some(text) => list[index]["parameters"][parameter] = to_value(text).unwrap(),
+            // The first command of the sequence may be of a different code, so it's never removed
+            None if index != item_indices[0] => removed.push(index),
+            None => list[index]["parameters"][parameter] = to_value("").unwrap(),
+        }
+    }
+
+    let mut template: Value = list[*filled_indices.last().unwrap()].clone();
+    template["code"] = to_value(&continuation).unwrap();
+
+    let text_command = |text: &String| -> Value {
+        let mut command: Value = template.clone();
+        command["parameters"][parameter] = to_value(text).unwrap();
+        command
+    };
+
+    let mut inserted: Vec<Value> = boxes[0].iter().skip(filled_indices.len()).map(text_command).collect();
+
+    for box_lines in boxes.iter().skip(1) {
+        inserted.push(header.clone().unwrap());
+        inserted.extend(box_lines.iter().map(text_command));
+    }
+
+    (*filled_indices.last().unwrap(), inserted)
+}
+
+/// Writes translation of the event commands of the list, which codes are allowed, using the translation of the section
+/// and the translation of speaker names.
+pub fn write_list(
     list: &mut Array,
     allowed_codes: &[u64],
+    location: &str,
     romanize: bool,
-    game_type: &Option<GameType>,
+    section: Section,
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
     map: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    speakers_map: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    placeholders: bool,
+    placeholders_mismatch_msg: &str,
+    wrap_width: Option<usize>,
+    wrap_lines: usize,
 ) {
     let list_length: usize = list.len();
 
-    let mut in_sequence: bool = false;
+    // Code of the commands, that continue current sequence of lines, and the kind and parameter of the sequence
+    let mut sequence: Option<(u64, Code, usize)> = None;
     let mut line: Vec<String> = Vec::with_capacity(256);
     let mut item_indices: Vec<usize> = Vec::with_capacity(256);
+    // Index of the first command with a non-empty line, by which the line is located when reading
+    let mut line_start: Option<usize> = None;
+    // Commands, that are removed from the list, and commands, that are added to the list after the command with the
+    // index, so the sequences of lines fit their translation
+    let mut removed: Vec<usize> = Vec::new();
+    let mut insertions: Vec<(usize, Vec<Value>)> = Vec::new();
 
     for it in 0..list_length {
-        let code: u64 = list[it]["code"].as_u64().unwrap();
+        // Commands without a code end the current sequence and are skipped, as when reading
+        let code: u64 = list[it]["code"].as_u64().unwrap_or_default();
 
-        if in_sequence && ![401, 405].contains(&code) {
-            if !line.is_empty() {
-                let mut joined: String = line.join("\n").trim().to_string();
+        if let Some((continuation, kind, parameter)) = sequence {
+            if code != continuation || !allowed_codes.contains(&code) {
+                if !line.is_empty() {
+                    let mut joined: String = join_sequence_lines(line.iter().map(String::as_str));
 
-                if romanize {
-                    joined = romanize_string(joined)
-                }
+                    if romanize {
+                        joined = romanize_string(joined)
+                    }
 
-                let translated: Option<String> = get_translated_parameter(Code::Dialogue, &joined, map, game_type);
+                    let translated: Option<String> = get_translated_text(
+                        kind,
+                        &joined,
+                        &format!("{location}:{}", line_start.unwrap_or(item_indices[0])),
+                        map,
+                        section,
+                        game_profile,
+                        rules,
+                        placeholders,
+                        placeholders_mismatch_msg,
+                    );
 
-                if let Some(translated) = translated {
-                    let split: Vec<&str> = translated.split('\n').collect();
-                    let split_length: usize = split.len();
-                    let line_length: usize = line.len();
+                    if let Some(translated) = translated {
+                        // Messages are rewrapped only if the wrapping width is specified
+                        let lines: Vec<String> = match (wrap_width, continuation) {
+                            (Some(width), 401) => wrap_text(&translated, width),
+                            _ => translated.split('\n').map(str::to_string).collect(),
+                        };
 
-                    for (i, &index) in item_indices.iter().enumerate() {
-                        if i < split_length {
-                            list[index]["parameters"][0] = to_value(split[i]).unwrap();
-                        } else {
-                            list[index]["parameters"][0] = to_value("").unwrap();
+                        let (index, inserted): (usize, Vec<Value>) = write_sequence(
+                            list,
+                            &item_indices,
+                            &line,
+                            parameter,
+                            continuation,
+                            &lines,
+                            wrap_width.map(|_| wrap_lines),
+                            &mut removed,
+                        );
+
+                        if !inserted.is_empty() {
+                            insertions.push((index, inserted));
                         }
                     }
 
-                    if split_length > line_length {
-                        let remaining: String = split[line_length - 1..].join("\n");
-
-                        list[*item_indices.last().unwrap()]["parameters"][0] = to_value(&remaining).unwrap();
-                    }
+                    line.clear();
+                    item_indices.clear();
+                    line_start = None;
                 }
 
-                line.clear();
-                item_indices.clear();
+                sequence = None;
             }
-
-            in_sequence = false
         }
 
         if !allowed_codes.contains(&code) {
             continue;
         }
 
-        if [401, 405].contains(&code) {
-            if let Some(parameter_str) = list[it]["parameters"][0].as_str() {
-                line.push(parameter_str.trim().to_string());
-                item_indices.push(it);
-                in_sequence = true;
+        let Some(rule) = get_code_rule(code) else {
+            continue;
+        };
+
+        match rule.extraction {
+            Extraction::Sequence {
+                parameter,
+                continuation,
+            } => {
+                if let Some(parameter_str) = list[it]["parameters"]
+                    .get(parameter)
+                    .and_then(|value: &Value| value.as_str())
+                {
+                    if !parameter_str.is_empty() && line_start.is_none() {
+                        line_start = Some(it);
+                    }
+
+                    line.push(parameter_str.to_string());
+                    item_indices.push(it);
+                }
+
+                sequence = Some((continuation, rule.kind, parameter));
             }
-        } else if list[it]["parameters"][0].is_array() {
-            for i in 0..list[it]["parameters"][0].as_array().unwrap().len() {
-                if let Some(subparameter_str) = list[it]["parameters"][0][i].as_str() {
-                    let mut subparameter_string = subparameter_str.to_string();
+            Extraction::Array(parameter) => {
+                let Some(array) = list[it]["parameters"][parameter].as_array_mut() else {
+                    continue;
+                };
 
-                    if romanize {
-                        subparameter_string = romanize_string(subparameter_string);
-                    }
+                for subparameter in array.iter_mut() {
+                    if let Some(subparameter_str) = subparameter.as_str() {
+                        let mut subparameter_string = subparameter_str.to_string();
 
-                    let translated: Option<String> =
-                        get_translated_parameter(Code::Dialogue, &subparameter_string, map, game_type);
+                        if romanize {
+                            subparameter_string = romanize_string(subparameter_string);
+                        }
 
-                    if let Some(translated) = translated {
-                        list[it]["parameters"][0][i] = to_value(&translated).unwrap();
+                        let translated: Option<String> = get_translated_text(
+                            rule.kind,
+                            &subparameter_string,
+                            &format!("{location}:{it}"),
+                            map,
+                            section,
+                            game_profile,
+                            rules,
+                            placeholders,
+                            placeholders_mismatch_msg,
+                        );
+
+                        if let Some(translated) = translated {
+                            *subparameter = to_value(&translated).unwrap();
+                        }
                     }
                 }
             }
-        } else if let Some(parameter_str) = list[it]["parameters"][0].as_str() {
-            let mut parameter_string: String = parameter_str.to_string();
+            Extraction::Parameter(parameter) => {
+                let Some(parameter_str) = list[it]["parameters"]
+                    .get(parameter)
+                    .and_then(|value: &Value| value.as_str())
+                else {
+                    continue;
+                };
+
+                let mut parameter_string: String = parameter_str.trim().to_string();
+
+                if romanize {
+                    parameter_string = romanize_string(parameter_string);
+                }
+
+                // Speaker names are translated in a separate section
+                let translated: Option<String> = get_translated_text(
+                    rule.kind,
+                    &parameter_string,
+                    &format!("{location}:{it}"),
+                    if rule.kind == Code::Speaker { speakers_map } else { map },
+                    section,
+                    game_profile,
+                    rules,
+                    placeholders,
+                    placeholders_mismatch_msg,
+                );
 
-            if romanize {
-                parameter_string = romanize_string(parameter_string);
+                if let Some(translated) = translated {
+                    list[it]["parameters"][parameter] = to_value(&translated).unwrap();
+                }
             }
+            Extraction::Script => {
+                let Some(script) = list[it]["parameters"][0].as_str() else {
+                    continue;
+                };
+
+                let mut translated_script: String = script.to_string();
+
+                // Literals are replaced from the end, so ranges of the previous ones stay valid
+                for (range, literal) in script_string_literals(script).into_iter().rev() {
+                    if rules.skips_parameter(section, rule.kind, literal) {
+                        continue;
+                    }
+
+                    let mut literal_string: String = literal.to_string();
 
-            let translated: Option<String> = get_translated_parameter(Code::System, &parameter_string, map, game_type);
+                    if romanize {
+                        literal_string = romanize_string(literal_string);
+                    }
+
+                    if let Some(translated) = get_translation(map, &format!("{location}:{it}"), &literal_string) {
+                        if !translated.is_empty() {
+                            replace_script_string_literal(&mut translated_script, range, translated);
+                        }
+                    }
+                }
 
-            if let Some(translated) = translated {
-                list[it]["parameters"][0] = to_value(&translated).unwrap();
+                list[it]["parameters"][0] = to_value(&translated_script).unwrap();
+            }
+            Extraction::PluginArguments => {
+                if list[it]["parameters"].get(3).is_some() {
+                    write_plugin_value(
+                        &mut list[it]["parameters"][3],
+                        &format!("{location}:{it}"),
+                        romanize,
+                        map,
+                        &|string: &str| rules.skips_parameter(section, rule.kind, string),
+                    );
+                }
             }
-        } else if let Some(parameter_str) = list[it]["parameters"][1].as_str() {
-            let mut parameter_string: String = parameter_str.to_string();
+            Extraction::PluginArgumentText => {
+                let Some((name, value)) = list[it]["parameters"][0]
+                    .as_str()
+                    .and_then(|text: &str| text.split_once(" = "))
+                else {
+                    continue;
+                };
 
-            if romanize {
-                parameter_string = romanize_string(parameter_string);
+                let mut value_string: String = value.to_string();
+
+                if romanize {
+                    value_string = romanize_string(value_string);
+                }
+
+                if let Some(translated) = get_translation(map, &format!("{location}:{it}"), &value_string) {
+                    if !translated.is_empty() {
+                        list[it]["parameters"][0] = to_value(&format!("{name} = {translated}")).unwrap();
+                    }
+                }
             }
+        }
+    }
 
-            let translated: Option<String> = get_translated_parameter(Code::Unknown, &parameter_string, map, game_type);
+    if removed.is_empty() && insertions.is_empty() {
+        return;
+    }
+
+    // List is rebuilt in a single pass, so indices of the commands stay valid while removing and inserting. Sequences
+    // are written in order, so both removed indices and insertions are sorted.
+    let commands: Array = std::mem::replace(list, Array::with_capacity(list_length));
+    let mut removed = removed.into_iter().peekable();
+    let mut insertions = insertions.into_iter().peekable();
+
+    for (i, command) in commands.into_iter().enumerate() {
+        if removed.next_if_eq(&i).is_none() {
+            list.push(command);
+        }
 
-            if let Some(translated) = translated {
-                list[it]["parameters"][1] = to_value(&translated).unwrap();
+        if let Some((_, inserted)) = insertions.next_if(|(index, _)| *index == i) {
+            for command in inserted {
+                list.push(command);
             }
         }
     }
 }
-/// Writes .txt files from maps folder back to their initial form.
+
+/// Writes translation files from maps folder back to their initial form.
 /// # Parameters
 /// * `maps_path` - path to the maps directory
 /// * `original_path` - path to the original directory
@@ -335,7 +692,14 @@ fn write_list(
 /// * `shuffle_level` - level of shuffle
 /// * `logging` - whether to log or not
 /// * `file_written_msg` - message to log when file is written
-/// * `game_type` - game type for custom parsing
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `format` - format of translation files
+/// * `codes` - codes of the event commands, which text is translated
+/// * `placeholders` - whether escape codes of event commands text were replaced with placeholders when reading
+/// * `placeholders_mismatch_msg` - message to log when translation isn't written, since its placeholders don't match
+/// * `wrap_width` - max width of message lines in characters, to which messages are rewrapped, if specified
+/// * `wrap_lines` - max number of lines in a message box, after which rewrapped messages are split
 pub fn write_maps(
     maps_path: &Path,
     original_path: &Path,
@@ -344,165 +708,144 @@ pub fn write_maps(
     shuffle_level: u8,
     logging: bool,
     file_written_msg: &str,
-    game_type: &Option<GameType>,
-) {
-    let maps_obj_vec: Vec<(String, Object)> = read_dir(original_path)
-        .unwrap()
-        .par_bridge()
-        .fold(
-            Vec::new,
-            |mut vec: Vec<(String, Object)>, entry: Result<DirEntry, _>| match entry {
-                Ok(entry) => {
-                    let filename: OsString = entry.file_name();
-                    let filename_str: &str = unsafe { from_utf8_unchecked(filename.as_encoded_bytes()) };
-
-                    let fourth_char: char;
-                    unsafe {
-                        fourth_char = *filename_str.as_bytes().get_unchecked(3) as char;
-                    }
-
-                    if filename_str.starts_with("Map") && fourth_char.is_ascii_digit() && filename_str.ends_with("json")
-                    {
-                        vec.push((
-                            filename_str.to_string(),
-                            from_str(&read_to_string(entry.path()).unwrap()).unwrap(),
-                        ))
-                    }
-                    vec
-                }
-                Err(_) => vec![],
-            },
-        )
-        .reduce(Vec::new, |mut a, b| {
-            a.extend(b);
-            a
-        });
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    format: Format,
+    codes: &[u64],
+    placeholders: bool,
+    placeholders_mismatch_msg: &str,
+    wrap_width: Option<usize>,
+    wrap_lines: usize,
+) -> Vec<Error> {
+    let mut errors: Vec<Error> = Vec::new();
+    let maps_obj_vec: Vec<(String, Object)> = read_game_files(original_path, is_map_file, &mut errors);
+
+    // Maps can be translated either in a single file, or in a separate file for every map
+    // Translation of the single maps file is only needed by the maps without their own translation file, so its error is
+    // reported by them
+    let maps_translation_map: Result<HashMap<String, String, BuildHasherDefault<Xxh3>>, Error> =
+        read_translation_map(maps_path, "maps", format, shuffle_level);
+
+    let (names_translation_map, speakers_translation_map) = match (
+        read_translation_map(maps_path, "names", format, shuffle_level),
+        read_translation_map(maps_path, "speakers", format, shuffle_level),
+    ) {
+        (Ok(names_translation_map), Ok(speakers_translation_map)) => (names_translation_map, speakers_translation_map),
+        (names_result, speakers_result) => {
+            errors.extend(names_result.err());
+            errors.extend(speakers_result.err());
+            return errors;
+        }
+    };
+    let map_sections: HashMap<String, String> = match find_map_sections(maps_path, format) {
+        Ok(map_sections) => map_sections,
+        Err(error) => {
+            errors.push(error);
+            return errors;
+        }
+    };
 
-    let maps_original_text_vec: Vec<String> = read_to_string(maps_path.join("maps.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-        .collect();
+    // Commands, which text is translated, are described by the table in codes module, shared by reading and writing
+    let allowed_codes: Vec<u64> = written_codes(codes);
 
-    let names_original_text_vec: Vec<String> = read_to_string(maps_path.join("names.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-        .collect();
+    let results: Vec<Result<(), Error>> = maps_obj_vec
+        .into_par_iter()
+        .map(|(filename, mut obj)| {
+            let map_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>>;
+            let translation_map: &HashMap<String, String, BuildHasherDefault<Xxh3>> =
+                match map_sections.get(filename.strip_suffix(".json").unwrap_or(&filename)) {
+                    Some(section_name) => {
+                        map_translation_map = read_translation_map(maps_path, section_name, format, shuffle_level)?;
+                        &map_translation_map
+                    }
+                    // Maps are skipped, and the error of the single maps file is reported only once
+                    None => match &maps_translation_map {
+                        Ok(maps_translation_map) => maps_translation_map,
+                        Err(_) => return Ok(()),
+                    },
+                };
 
-    let mut maps_translated_text_vec: Vec<String> = read_to_string(maps_path.join("maps_trans.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-        .collect();
+            if let Some(display_name) = obj.get(&"displayName").and_then(|value: &Value| value.as_str()) {
+                let mut display_name: String = display_name.to_string();
 
-    let mut names_translated_text_vec: Vec<String> = read_to_string(maps_path.join("names_trans.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-        .collect();
+                if romanize {
+                    display_name = romanize_string(display_name)
+                }
 
-    match shuffle_level {
-        1 => {
-            shuffle(&mut maps_translated_text_vec);
-            shuffle(&mut names_translated_text_vec);
-        }
-        2 => {
-            for (translated_text, translated_name_text) in maps_translated_text_vec
-                .iter_mut()
-                .zip(names_translated_text_vec.iter_mut())
-            {
-                *translated_text = shuffle_words(translated_text);
-                *translated_name_text = shuffle_words(translated_name_text);
+                if let Some(location_name) = get_translation(
+                    &names_translation_map,
+                    &format!("{filename}:displayName"),
+                    &display_name,
+                ) {
+                    if !location_name.is_empty() {
+                        obj["displayName"] = to_value(location_name).unwrap();
+                    }
+                }
             }
-        }
-        _ => {}
-    }
 
-    let maps_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> = maps_original_text_vec
-        .into_par_iter()
-        .zip(maps_translated_text_vec.into_par_iter())
-        .fold(
-            HashMap::default,
-            |mut map: HashMap<String, String, BuildHasherDefault<Xxh3>>, (key, value): (String, String)| {
-                map.insert(key, value);
-                map
-            },
-        )
-        .reduce(HashMap::default, |mut a, b| {
-            a.extend(b);
-            a
-        });
+            // Maps without events, events without pages and pages without lists are skipped, as when reading
+            let events: &mut [Value] = obj
+                .get_mut(&"events")
+                .and_then(|value: &mut Value| value.as_array_mut())
+                .map_or(&mut [], |events: &mut Array| events.as_mut_slice());
 
-    let names_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> = names_original_text_vec
-        .into_par_iter()
-        .zip(names_translated_text_vec.into_par_iter())
-        .fold(
-            HashMap::default,
-            |mut map: HashMap<String, String, BuildHasherDefault<Xxh3>>, (key, value): (String, String)| {
-                map.insert(key, value);
-                map
-            },
-        )
-        .reduce(HashMap::default, |mut a, b| {
-            a.extend(b);
-            a
-        });
+            events
+                .par_iter_mut()
+                .enumerate()
+                .skip(1) //Skipping first element in array as it is null
+                .for_each(|(event_index, event): (usize, &mut Value)| {
+                    let event_id: u64 = event["id"].as_u64().unwrap_or(event_index as u64);
+
+                    let Some(pages) = event
+                        .get_mut("pages")
+                        .and_then(|value: &mut Value| value.as_array_mut())
+                    else {
+                        return;
+                    };
 
-    // 401 - dialogue lines
-    // 102 - dialogue choices array
-    // 402 - one of the dialogue choices from the array
-    // 356 - system lines (special texts)
-    // 324 - i don't know what is it but it's some used in-game lines
-    const ALLOWED_CODES: [u64; 5] = [401, 102, 402, 356, 324];
+                    pages
+                        .par_iter_mut()
+                        .enumerate()
+                        .for_each(|(page_index, page): (usize, &mut Value)| {
+                            let Some(list) = page.get_mut("list").and_then(|value: &mut Value| value.as_array_mut())
+                            else {
+                                return;
+                            };
 
-    maps_obj_vec.into_par_iter().for_each(|(filename, mut obj)| {
-        {
-            let mut display_name: String = obj["displayName"].as_str().unwrap().to_string();
+                            write_list(
+                                list,
+                                &allowed_codes,
+                                &format!("{filename}:{event_id}:{page_index}"),
+                                romanize,
+                                Section::Maps,
+                                game_profile,
+                                rules,
+                                translation_map,
+                                &speakers_translation_map,
+                                placeholders,
+                                placeholders_mismatch_msg,
+                                wrap_width,
+                                wrap_lines,
+                            );
+                        });
+                });
 
-            if romanize {
-                display_name = romanize_string(display_name)
-            }
+            write_file(&output_path.join(&filename), to_string(&obj).unwrap())?;
 
-            if let Some(location_name) = names_translation_map.get(&display_name) {
-                obj["displayName"] = to_value(location_name).unwrap();
+            if logging {
+                println!("{file_written_msg} {filename}");
             }
-        }
-
-        obj["events"]
-            .as_array_mut()
-            .unwrap()
-            .par_iter_mut()
-            .skip(1) //Skipping first element in array as it is null
-            .for_each(|event: &mut Value| {
-                if event.is_null() {
-                    return;
-                }
-
-                event["pages"]
-                    .as_array_mut()
-                    .unwrap()
-                    .par_iter_mut()
-                    .for_each(|page: &mut Value| {
-                        write_list(
-                            page["list"].as_array_mut().unwrap(),
-                            &ALLOWED_CODES,
-                            romanize,
-                            game_type,
-                            &maps_translation_map,
-                        );
-                    });
-            });
 
-        write(output_path.join(&filename), to_string(&obj).unwrap()).unwrap();
+            Ok(())
+        })
+        .collect();
 
-        if logging {
-            println!("{file_written_msg} {filename}");
-        }
-    });
+    errors.extend(maps_translation_map.err());
+    errors.extend(results.into_iter().filter_map(Result::err));
+    errors
 }
 
-/// Writes .txt files from other folder back to their initial form.
+/// Writes translation files from other folder back to their initial form.
 /// # Parameters
 /// * `other_path` - path to the other directory
 /// * `original_path` - path to the original directory
@@ -511,7 +854,14 @@ pub fn write_maps(
 /// * `shuffle_level` - level of shuffle
 /// * `logging` - whether to log or not
 /// * `file_written_msg` - message to log when file is written
-/// * `game_type` - game type for custom parsing
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `format` - format of translation files
+/// * `codes` - codes of the event commands, which text is translated
+/// * `placeholders` - whether escape codes of event commands text were replaced with placeholders when reading
+/// * `placeholders_mismatch_msg` - message to log when translation isn't written, since its placeholders don't match
+/// * `wrap_width` - max width of message lines in characters, to which messages are rewrapped, if specified
+/// * `wrap_lines` - max number of lines in a message box, after which rewrapped messages are split
 pub fn write_other(
     other_path: &Path,
     original_path: &Path,
@@ -520,191 +870,259 @@ pub fn write_other(
     shuffle_level: u8,
     logging: bool,
     file_written_msg: &str,
-    game_type: &Option<GameType>,
-) {
-    let other_obj_arr_vec: Vec<(String, Array)> = read_dir(original_path)
-        .unwrap()
-        .par_bridge()
-        .fold(
-            Vec::new,
-            |mut vec: Vec<(String, Array)>, entry: Result<DirEntry, _>| match entry {
-                Ok(entry) => {
-                    let filename_os_string: OsString = entry.file_name();
-                    let filename: &str = unsafe { from_utf8_unchecked(filename_os_string.as_encoded_bytes()) };
-                    let (real_name, extension) = filename.split_once('.').unwrap();
-
-                    if !real_name.starts_with("Map")
-                        && !matches!(real_name, "Tilesets" | "Animations" | "States" | "System")
-                        && extension == "json"
-                    {
-                        vec.push((
-                            filename.to_string(),
-                            from_str(&read_to_string(entry.path()).unwrap()).unwrap(),
-                        ));
-                    }
-                    vec
-                }
-                Err(_) => vec![],
-            },
-        )
-        .reduce(Vec::new, |mut a, b| {
-            a.extend(b);
-            a
-        });
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    format: Format,
+    codes: &[u64],
+    placeholders: bool,
+    placeholders_mismatch_msg: &str,
+    wrap_width: Option<usize>,
+    wrap_lines: usize,
+) -> Vec<Error> {
+    let mut errors: Vec<Error> = Vec::new();
+    let other_obj_arr_vec: Vec<(String, Array)> = read_game_files(original_path, is_other_file, &mut errors);
+
+    // Commands, which text is translated, are described by the table in codes module, shared by reading and writing
+    let allowed_codes: Vec<u64> = written_codes(codes);
+
+    // Speakers of common events and troops are translated in a single section
+    let speakers_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> =
+        match read_translation_map(other_path, "speakers", format, shuffle_level) {
+            Ok(speakers_translation_map) => speakers_translation_map,
+            Err(error) => {
+                errors.push(error);
+                return errors;
+            }
+        };
 
-    // 401 - dialogue lines
-    // 405 - credits lines
-    // 102 - dialogue choices array
-    // 402 - one of the dialogue choices from the array
-    // 356 - system lines (special texts)
-    // 324 - i don't know what is it but it's some used in-game lines
-    const ALLOWED_CODES: [u64; 6] = [401, 402, 405, 356, 102, 324];
+    let results: Vec<Result<(), Error>> = other_obj_arr_vec
+        .into_par_iter()
+        .map(|(filename, mut obj_arr)| {
+            let other_processed_filename: String = filename[..filename.len() - 5].to_lowercase();
 
-    other_obj_arr_vec.into_par_iter().for_each(|(filename, mut obj_arr)| {
-        let other_processed_filename: &str = &filename[..filename.len() - 5];
+            let (mut other_original_text, mut other_translated_text): (Vec<String>, Vec<String>) =
+                read_translation_lines(other_path, &other_processed_filename, format)?;
 
-        let other_original_text: Vec<String> =
-            read_to_string(other_path.join(format!("{other_processed_filename}.txt")))
-                .unwrap()
-                .par_split('\n')
-                .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-                .collect();
-
-        let mut other_translated_text: Vec<String> =
-            read_to_string(other_path.join(format!("{other_processed_filename}_trans.txt")))
-                .unwrap()
-                .par_split('\n')
-                .map(|line: &str| line.replace(r"\#", "\n").trim().to_string())
-                .collect();
-
-        match shuffle_level {
-            1 => {
-                shuffle(&mut other_translated_text);
+            for line in other_original_text.iter_mut().chain(other_translated_text.iter_mut()) {
+                *line = line.replace(r"\#", "\n").trim().to_string();
             }
-            2 => {
-                for translated_text in other_translated_text.iter_mut() {
-                    *translated_text = shuffle_words(translated_text);
+
+            match shuffle_level {
+                1 => {
+                    shuffle(&mut other_translated_text);
+                }
+                2 => {
+                    for translated_text in other_translated_text.iter_mut() {
+                        *translated_text = shuffle_words(translated_text);
+                    }
                 }
+                _ => {}
             }
-            _ => {}
-        }
-
-        let other_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> = other_original_text
-            .into_par_iter()
-            .zip(other_translated_text.into_par_iter())
-            .fold(
-                HashMap::default,
-                |mut map: HashMap<String, String, BuildHasherDefault<Xxh3>>, (key, value): (String, String)| {
-                    map.insert(key, value);
-                    map
-                },
-            )
-            .reduce(HashMap::default, |mut a, b| {
-                a.extend(b);
-                a
-            });
 
-        // Other files except CommonEvents.json and Troops.json have the structure that consists
-        // of name, nickname, description and note
-        if !filename.starts_with("Co") && !filename.starts_with("Tr") {
-            obj_arr
-                .par_iter_mut()
-                .skip(1) // Skipping first element in array as it is null
-                .for_each(|obj: &mut Value| {
-                    for (variable_label, variable_type) in [
-                        ("name", Variable::Name),
-                        ("nickname", Variable::Nickname),
-                        ("description", Variable::Description),
-                        ("message1", Variable::Message1),
-                        ("message2", Variable::Message2),
-                        ("message3", Variable::Message3),
-                        ("message4", Variable::Message4),
-                        ("note", Variable::Note),
-                    ] {
-                        if let Some(variable_str) = obj[variable_label].as_str() {
-                            let mut variable_text: String = if variable_type != Variable::Note {
-                                variable_str.trim().to_string()
-                            } else {
-                                variable_str.to_string()
-                            };
+            let other_translation_map: HashMap<String, String, BuildHasherDefault<Xxh3>> = other_original_text
+                .into_par_iter()
+                .zip(other_translated_text.into_par_iter())
+                .fold(
+                    HashMap::default,
+                    |mut map: HashMap<String, String, BuildHasherDefault<Xxh3>>, (key, value): (String, String)| {
+                        map.insert(key, value);
+                        map
+                    },
+                )
+                .reduce(HashMap::default, |mut a, b| {
+                    a.extend(b);
+                    a
+                });
 
-                            if !variable_text.is_empty() {
-                                if romanize {
-                                    variable_text = romanize_string(variable_text)
-                                }
+            // Fields of the objects, that are translated, are described by the table in the crate root, shared by
+            // reading and writing
+            let fields: &DatabaseFields = database_fields(&filename);
 
-                                variable_text = variable_text
-                                    .split('\n')
-                                    .map(|line: &str| line.trim())
-                                    .collect::<Vec<_>>()
-                                    .join("\n");
-
-                                let note_text: Option<&str> = if game_type.is_some()
-                                    && *game_type.as_ref().unwrap() != GameType::Termina
-                                    && variable_type != Variable::Description
-                                {
-                                    None
+            // Other files except CommonEvents.json and Troops.json consist of objects with fields only
+            if !filename.starts_with("Co") && !filename.starts_with("Tr") {
+                obj_arr
+                    .par_iter_mut()
+                    .skip(1) // Skipping first element in array as it is null
+                    .for_each(|obj: &mut Value| {
+                        let id: u64 = obj["id"].as_u64().unwrap_or_default();
+
+                        for &(variable_label, variable_type) in fields.fields {
+                            if let Some(variable_str) = obj[variable_label].as_str() {
+                                let mut variable_text: String = if variable_type != Variable::Note {
+                                    variable_str.trim().to_string()
                                 } else {
-                                    match obj.get("note") {
-                                        Some(value) => value.as_str(),
-                                        None => None,
-                                    }
+                                    variable_str.to_string()
                                 };
 
-                                let translated: Option<String> = get_translated_variable(
-                                    variable_text,
-                                    note_text,
-                                    variable_type,
-                                    &filename,
-                                    &other_translation_map,
-                                    game_type,
-                                );
+                                if !variable_text.is_empty() {
+                                    if romanize {
+                                        variable_text = romanize_string(variable_text)
+                                    }
 
-                                if let Some(translated) = translated {
-                                    obj[variable_label] = to_value(&translated).unwrap();
+                                    variable_text = variable_text
+                                        .split('\n')
+                                        .map(|line: &str| line.trim())
+                                        .collect::<Vec<_>>()
+                                        .join("\n");
+
+                                    let note_text: Option<&str> = if variable_type == Variable::Description {
+                                        obj.get("note").and_then(|value: &Value| value.as_str())
+                                    } else {
+                                        None
+                                    };
+
+                                    // Text inside the note tags is translated in place
+                                    let has_note_tags: bool =
+                                        variable_type == Variable::Note && rules.has_note_tags(&filename, game_profile);
+
+                                    let translated: Option<String> = get_translated_variable(
+                                        variable_text,
+                                        note_text,
+                                        variable_type,
+                                        &filename,
+                                        &format!("{filename}:{id}:{variable_label}"),
+                                        &other_translation_map,
+                                        game_profile,
+                                        rules,
+                                    );
+
+                                    let translated: Option<String> = if has_note_tags {
+                                        Some(translate_note_tags(
+                                            translated.unwrap_or_else(|| variable_str.to_string()),
+                                            &filename,
+                                            &format!("{filename}:{id}:note"),
+                                            &other_translation_map,
+                                            romanize,
+                                            game_profile,
+                                            rules,
+                                        ))
+                                    } else if matches!(
+                                        variable_type,
+                                        Variable::Message1
+                                            | Variable::Message2
+                                            | Variable::Message3
+                                            | Variable::Message4
+                                    ) && variable_str.starts_with(' ')
+                                    {
+                                        // Messages of MV are appended to the name of the battler, so they start with a
+                                        // space, while messages of MZ contain the name as a placeholder
+                                        translated.map(|translated: String| " ".to_owned() + &translated)
+                                    } else {
+                                        translated
+                                    };
+
+                                    if let Some(translated) = translated {
+                                        obj[variable_label] = to_value(&translated).unwrap();
+                                    }
                                 }
                             }
                         }
-                    }
-                });
-        } else {
-            //Other files have the structure somewhat similar to Maps.json files
-            obj_arr
-                .par_iter_mut()
-                .skip(1) //Skipping first element in array as it is null
-                .for_each(|obj: &mut Value| {
-                    //CommonEvents doesn't have pages, so we can just check if it's Troops
-                    let pages_length: usize = if filename.starts_with("Troops") {
-                        obj["pages"].as_array().unwrap().len()
-                    } else {
-                        1
-                    };
 
-                    for i in 0..pages_length {
-                        //If element has pages, then we'll iterate over them
-                        //Otherwise we'll just iterate over the list
-                        let list_value: &mut Value = if pages_length != 1 {
-                            &mut obj["pages"][i]["list"]
+                        for &(array_label, nested_fields) in fields.nested {
+                            let Some(array) = obj[array_label].as_array_mut() else {
+                                continue;
+                            };
+
+                            for (i, nested_obj) in array.iter_mut().enumerate() {
+                                for &(variable_label, variable_type) in nested_fields {
+                                    let translated: Option<String> =
+                                        nested_obj[variable_label].as_str().and_then(|variable_str: &str| {
+                                            get_translated_field(
+                                                variable_str,
+                                                variable_type,
+                                                &format!("{filename}:{id}:{array_label}:{i}:{variable_label}"),
+                                                &other_translation_map,
+                                                romanize,
+                                                rules,
+                                            )
+                                        });
+
+                                    if let Some(translated) = translated {
+                                        nested_obj[variable_label] = to_value(&translated).unwrap();
+                                    }
+                                }
+                            }
+                        }
+                    });
+            } else {
+                //Other files have the structure somewhat similar to Maps.json files
+                obj_arr
+                    .par_iter_mut()
+                    .enumerate()
+                    .skip(1) //Skipping first element in array as it is null
+                    .for_each(|(obj_index, obj): (usize, &mut Value)| {
+                        let id: u64 = obj["id"].as_u64().unwrap_or(obj_index as u64);
+
+                        for &(variable_label, variable_type) in fields.fields {
+                            let translated: Option<String> =
+                                obj[variable_label].as_str().and_then(|variable_str: &str| {
+                                    get_translated_field(
+                                        variable_str,
+                                        variable_type,
+                                        &format!("{filename}:{id}:{variable_label}"),
+                                        &other_translation_map,
+                                        romanize,
+                                        rules,
+                                    )
+                                });
+
+                            if let Some(translated) = translated {
+                                obj[variable_label] = to_value(&translated).unwrap();
+                            }
+                        }
+
+                        //CommonEvents doesn't have pages, so we can just check if it's Troops
+                        let pages_length: usize = if filename.starts_with("Troops") {
+                            obj["pages"].as_array().map_or(0, |pages: &Array| pages.len())
                         } else {
-                            &mut obj["list"]
+                            1
                         };
 
-                        if let Some(list) = list_value.as_array_mut() {
-                            write_list(list, &ALLOWED_CODES, romanize, game_type, &other_translation_map);
+                        for i in 0..pages_length {
+                            //If element has pages, then we'll iterate over them
+                            //Otherwise we'll just iterate over the list
+                            let list_value: &mut Value = if filename.starts_with("Troops") {
+                                &mut obj["pages"][i]["list"]
+                            } else {
+                                &mut obj["list"]
+                            };
+
+                            if let Some(list) = list_value.as_array_mut() {
+                                write_list(
+                                    list,
+                                    &allowed_codes,
+                                    &format!("{filename}:{id}:{i}"),
+                                    romanize,
+                                    Section::Other,
+                                    game_profile,
+                                    rules,
+                                    &other_translation_map,
+                                    &speakers_translation_map,
+                                    placeholders,
+                                    placeholders_mismatch_msg,
+                                    wrap_width,
+                                    wrap_lines,
+                                );
+                            }
                         }
-                    }
-                });
-        }
+                    });
+            }
 
-        write(output_path.join(&filename), to_string(&obj_arr).unwrap()).unwrap();
+            write_file(&output_path.join(&filename), to_string(&obj_arr).unwrap())?;
 
-        if logging {
-            println!("{file_written_msg} {filename}");
-        }
-    });
+            if logging {
+                println!("{file_written_msg} {filename}");
+            }
+
+            Ok(())
+        })
+        .collect();
+
+    errors.extend(results.into_iter().filter_map(Result::err));
+    errors
 }
 
-/// Writes system.txt file back to its initial form.
+/// Writes system translation file back to its initial form.
 ///
 /// For inner code documentation, check read_system function.
 /// # Parameters
@@ -715,6 +1133,8 @@ pub fn write_other(
 /// * `shuffle_level` - level of shuffle
 /// * `logging` - whether to log or not
 /// * `file_written_msg` - message to log when file is written
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `format` - format of translation files
 pub fn write_system(
     system_file_path: &Path,
     other_path: &Path,
@@ -723,28 +1143,17 @@ pub fn write_system(
     shuffle_level: u8,
     logging: bool,
     file_written_msg: &str,
-) {
-    let mut system_obj: Object = from_str(&read_to_string(system_file_path).unwrap()).unwrap();
+    rules: &Rules,
+    format: Format,
+) -> Result<(), Error> {
+    let mut system_obj: Object = read_json(system_file_path)?;
 
-    let system_original_text: Vec<String> = read_to_string(other_path.join("system.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.trim().to_string())
-        .collect();
+    let (mut system_original_text, mut system_translated_text): (Vec<String>, Vec<String>) =
+        read_translation_lines(other_path, "system", format)?;
 
-    let system_translated_text: (String, String) = read_to_string(other_path.join("system_trans.txt"))
-        .unwrap()
-        .rsplit_once('\n')
-        .map(|(left, right)| (left.to_string(), right.to_string()))
-        .unwrap();
-
-    let game_title: String = system_translated_text.1;
-
-    let mut system_translated_text: Vec<String> = system_translated_text
-        .0
-        .par_split('\n')
-        .map(|line: &str| line.trim().to_string())
-        .collect();
+    for line in system_original_text.iter_mut().chain(system_translated_text.iter_mut()) {
+        *line = line.trim().to_string();
+    }
 
     match shuffle_level {
         1 => {
@@ -773,106 +1182,72 @@ pub fn write_system(
             a
         });
 
-    system_obj["armorTypes"]
-        .as_array_mut()
-        .unwrap()
-        .par_iter_mut()
-        .for_each(|value: &mut Value| {
-            let mut string: String = value.as_str().unwrap().trim().to_string();
-
-            if romanize {
-                string = romanize_string(string);
-            }
-
-            if let Some(translated) = system_translation_map.get(&string) {
-                if translated.is_empty() {
+    for key in ["armorTypes", "elements", "equipTypes", "skillTypes", "weaponTypes"] {
+        system_obj
+            .get_mut(&key)
+            .and_then(|value: &mut Value| value.as_array_mut())
+            .ok_or_else(|| invalid_value(system_file_path, key))?
+            .par_iter_mut()
+            .enumerate()
+            .for_each(|(i, value): (usize, &mut Value)| {
+                let Some(str) = value.as_str().map(str::trim) else {
                     return;
-                }
-
-                *value = to_value(translated).unwrap();
-            }
-        });
-
-    system_obj["elements"]
-        .as_array_mut()
-        .unwrap()
-        .par_iter_mut()
-        .for_each(|value: &mut Value| {
-            let mut string: String = value.as_str().unwrap().trim().to_string();
-
-            if romanize {
-                string = romanize_string(string);
-            }
+                };
 
-            if let Some(translated) = system_translation_map.get(&string) {
-                if translated.is_empty() {
+                if rules.skips_string(Section::System, str) {
                     return;
                 }
 
-                *value = to_value(translated).unwrap();
-            }
-        });
+                let mut string: String = str.to_string();
 
-    system_obj["equipTypes"]
-        .as_array_mut()
-        .unwrap()
-        .par_iter_mut()
-        .for_each(|value: &mut Value| {
-            let mut string: String = value.as_str().unwrap().trim().to_string();
-
-            if romanize {
-                string = romanize_string(string);
-            }
-
-            if let Some(translated) = system_translation_map.get(&string) {
-                if translated.is_empty() {
-                    return;
+                if romanize {
+                    string = romanize_string(string);
                 }
 
-                *value = to_value(translated).unwrap();
-            }
-        });
-
-    system_obj["skillTypes"]
-        .as_array_mut()
-        .unwrap()
-        .par_iter_mut()
-        .for_each(|value: &mut Value| {
-            let mut string: String = value.as_str().unwrap().trim().to_string();
-
-            if romanize {
-                string = romanize_string(string);
-            }
+                if let Some(translated) =
+                    get_translation(&system_translation_map, &format!("System.json:{key}:{i}"), &string)
+                {
+                    if translated.is_empty() {
+                        return;
+                    }
 
-            if let Some(translated) = system_translation_map.get(&string) {
-                if translated.is_empty() {
-                    return;
+                    *value = to_value(translated).unwrap();
                 }
+            });
+    }
 
-                *value = to_value(translated).unwrap();
-            }
-        });
-
-    system_obj["terms"]
-        .as_object_mut()
-        .unwrap()
+    system_obj
+        .get_mut(&"terms")
+        .and_then(|value: &mut Value| value.as_object_mut())
+        .ok_or_else(|| invalid_value(system_file_path, "terms"))?
         .iter_mut()
         .par_bridge()
         .for_each(|(key, value): (&str, &mut Value)| {
             if key != "messages" {
-                value
-                    .as_array_mut()
-                    .unwrap()
+                let Some(array) = value.as_array_mut() else {
+                    return;
+                };
+
+                array
                     .par_iter_mut()
-                    .for_each(|subvalue: &mut Value| {
-                        if let Some(str) = subvalue.as_str() {
-                            let mut string: String = str.trim().to_string();
+                    .enumerate()
+                    .for_each(|(i, subvalue): (usize, &mut Value)| {
+                        if let Some(str) = subvalue.as_str().map(str::trim) {
+                            if rules.skips_string(Section::System, str) {
+                                return;
+                            }
+
+                            let mut string: String = str.to_string();
 
                             if romanize {
                                 string = romanize_string(string);
                             }
 
-                            if let Some(translated) = system_translation_map.get(&string) {
+                            if let Some(translated) = get_translation(
+                                &system_translation_map,
+                                &format!("System.json:terms:{key}:{i}"),
+                                &string,
+                            ) {
                                 if translated.is_empty() {
                                     return;
                                 }
@@ -882,23 +1257,33 @@ pub fn write_system(
                         }
                     });
             } else {
-                if !value.is_object() {
+                let Some(messages) = value.as_object_mut() else {
                     return;
-                }
+                };
 
-                value
-                    .as_object_mut()
-                    .unwrap()
+                messages
                     .iter_mut()
                     .par_bridge()
-                    .for_each(|(_, value)| {
-                        let mut string: String = value.as_str().unwrap().trim().to_string();
+                    .for_each(|(message_key, value): (&str, &mut Value)| {
+                        let Some(str) = value.as_str().map(str::trim) else {
+                            return;
+                        };
+
+                        if rules.skips_string(Section::System, str) {
+                            return;
+                        }
+
+                        let mut string: String = str.to_string();
 
                         if romanize {
                             string = romanize_string(string)
                         }
 
-                        if let Some(translated) = system_translation_map.get(&string) {
+                        if let Some(translated) = get_translation(
+                            &system_translation_map,
+                            &format!("System.json:terms:messages:{message_key}"),
+                            &string,
+                        ) {
                             if translated.is_empty() {
                                 return;
                             }
@@ -909,64 +1294,129 @@ pub fn write_system(
             }
         });
 
-    system_obj["weaponTypes"]
-        .as_array_mut()
-        .unwrap()
-        .par_iter_mut()
-        .for_each(|value: &mut Value| {
-            let mut string: String = value.as_str().unwrap().trim().to_string();
+    {
+        let mut game_title: String = system_obj
+            .get(&"gameTitle")
+            .and_then(|value: &Value| value.as_str())
+            .ok_or_else(|| invalid_value(system_file_path, "gameTitle"))?
+            .trim()
+            .to_string();
+
+        if romanize {
+            game_title = romanize_string(game_title);
+        }
 
-            if romanize {
-                string = romanize_string(string);
+        if let Some(translated) = get_translation(&system_translation_map, "System.json:gameTitle", &game_title) {
+            if !translated.is_empty() {
+                system_obj["gameTitle"] = to_value(translated).unwrap();
             }
+        }
+    }
 
-            if let Some(translated) = system_translation_map.get(&string) {
-                if translated.is_empty() {
-                    return;
-                }
+    write_file(&output_path.join("System.json"), to_string(&system_obj).unwrap())?;
+
+    if logging {
+        println!("{file_written_msg} System.json");
+    }
+
+    Ok(())
+}
+
+/// Recursively replaces strings of plugin parameter value with their translations, re-encoding JSON-encoded structs
+/// and lists back to strings. Strings, for which `skips` returns true, are left as is, as they're skipped when reading.
+/// Returns whether anything was replaced, so untouched parameters keep their exact encoding.
+fn write_plugin_value<F: Fn(&str) -> bool>(
+    value: &mut Value,
+    location: &str,
+    romanize: bool,
+    map: &HashMap<String, String, BuildHasherDefault<Xxh3>>,
+    skips: &F,
+) -> bool {
+    if let Some(str) = value.as_str() {
+        if let Some(mut decoded) = decode_plugin_parameter(str) {
+            if write_plugin_value(&mut decoded, location, romanize, map, skips) {
+                *value = to_value(&to_string(&decoded).unwrap()).unwrap();
+                return true;
+            }
 
+            return false;
+        }
+
+        if skips(str) {
+            return false;
+        }
+
+        let mut string: String = str.to_string();
+
+        if romanize {
+            string = romanize_string(string);
+        }
+
+        if let Some(translated) = get_translation(map, location, &string) {
+            if !translated.is_empty() && translated != str {
                 *value = to_value(translated).unwrap();
+                return true;
             }
-        });
+        }
 
-    system_obj["gameTitle"] = to_value(&game_title).unwrap();
+        false
+    } else if let Some(array) = value.as_array_mut() {
+        let mut replaced: bool = false;
 
-    write(output_path.join("System.json"), to_string(&system_obj).unwrap()).unwrap();
+        for value in array.iter_mut() {
+            replaced |= write_plugin_value(value, location, romanize, map, skips);
+        }
 
-    if logging {
-        println!("{file_written_msg} System.json");
+        replaced
+    } else if let Some(object) = value.as_object_mut() {
+        let mut replaced: bool = false;
+
+        for (_, value) in object.iter_mut() {
+            replaced |= write_plugin_value(value, location, romanize, map, skips);
+        }
+
+        replaced
+    } else {
+        false
     }
 }
 
-/// Writes plugins.txt file back to its initial form. Currently works only if game_type is GameType::Termina.
+/// Writes plugins translation file back to its initial form.
 /// # Parameters
 /// * `plugins_file_path` - path to the original plugins file
 /// * `plugins_path` - path to the plugins directory
 /// * `output_path` - path to the output directory
+/// * `romanize` - if files were read with romanize, this option will romanize original game text to compare with parsed
 /// * `shuffle_level` - level of shuffle
 /// * `logging` - whether to log or not
 /// * `file_written_msg` - message to log when file is written
+/// * `game_profile` - profile of the game for custom processing
+/// * `rules` - user-defined rules, that decide which strings are translatable
+/// * `format` - format of translation files
 pub fn write_plugins(
-    pluigns_file_path: &Path,
+    plugins_file_path: &Path,
     plugins_path: &Path,
     output_path: &Path,
+    romanize: bool,
     shuffle_level: u8,
     logging: bool,
     file_written_msg: &str,
-) {
-    let mut obj_arr: Vec<Object> = from_str(&read_to_string(pluigns_file_path).unwrap()).unwrap();
+    game_profile: &Option<GameProfile>,
+    rules: &Rules,
+    format: Format,
+) -> Result<(), Error> {
+    let content: String = read_file(plugins_file_path)?;
+    let mut obj_arr: Vec<Object> = parse_json(plugins_file_path, strip_plugins_prefix(&content))?;
 
-    let plugins_original_text: Vec<String> = read_to_string(plugins_path.join("plugins.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.to_string())
-        .collect();
+    let (mut plugins_original_text, mut plugins_translated_text): (Vec<String>, Vec<String>) =
+        read_translation_lines(plugins_path, "plugins", format)?;
 
-    let mut plugins_translated_text: Vec<String> = read_to_string(plugins_path.join("plugins_trans.txt"))
-        .unwrap()
-        .par_split('\n')
-        .map(|line: &str| line.to_string())
-        .collect();
+    for line in plugins_original_text
+        .iter_mut()
+        .chain(plugins_translated_text.iter_mut())
+    {
+        *line = line.replace(r"\#", "\n");
+    }
 
     match shuffle_level {
         1 => {
@@ -996,75 +1446,187 @@ pub fn write_plugins(
         });
 
     obj_arr.par_iter_mut().for_each(|obj: &mut Object| {
-        // For now, plugins writing only implemented for Fear & Hunger: Termina, so you should manually translate the plugins.js file if it's not Termina
-
-        // Plugins with needed text
-        let plugin_names: HashSet<&str, BuildHasherDefault<Xxh3>> = HashSet::from_iter([
-            "YEP_BattleEngineCore",
-            "YEP_OptionsCore",
-            "SRD_NameInputUpgrade",
-            "YEP_KeyboardConfig",
-            "YEP_ItemCore",
-            "YEP_X_ItemDiscard",
-            "YEP_EquipCore",
-            "YEP_ItemSynthesis",
-            "ARP_CommandIcons",
-            "YEP_X_ItemCategories",
-            "Olivia_OctoBattle",
-        ]);
-
-        let name: &str = obj["name"].as_str().unwrap();
+        let name: String = obj["name"].as_str().unwrap_or_default().to_string();
 
-        // It it's a plugin with the needed text, proceed
-        if plugin_names.contains(name) {
-            //YEP_OptionsCore should be processed differently, as its parameters is a mess, that can't even be parsed to json
-            if name == "YEP_OptionsCore" {
-                obj["parameters"]
-                    .as_object_mut()
-                    .unwrap()
-                    .iter_mut()
-                    .par_bridge()
-                    .for_each(|(key, value): (&str, &mut Value)| {
-                        let mut string: String = value.as_str().unwrap().to_string();
-
-                        if key == "OptionsCategories" {
-                            for (text, translated) in
-                                plugins_translation_map.keys().zip(plugins_translation_map.values())
-                            {
-                                string = string.replacen(text, translated, 1);
-                            }
-
-                            *value = to_value(&string).unwrap();
-                        } else if let Some(translated) = plugins_translation_map.get(&string) {
-                            *value = to_value(translated).unwrap();
-                        }
-                    });
-            }
-            // Everything else is an easy walk
-            else {
-                obj["parameters"]
-                    .as_object_mut()
-                    .unwrap()
-                    .iter_mut()
-                    .par_bridge()
-                    .for_each(|(_, value)| {
-                        if let Some(str) = value.as_str() {
-                            if let Some(translated) = plugins_translation_map.get(str) {
-                                *value = to_value(translated).unwrap();
-                            }
-                        }
-                    });
-            }
+        // Some games have a lot of plugins with untranslatable strings, so only the plugins from their profiles are written
+        if game_profile
+            .as_ref()
+            .is_some_and(|game_profile: &GameProfile| !game_profile.parses_plugin(&name))
+        {
+            return;
         }
+
+        let parameters: &mut Object = match obj["parameters"].as_object_mut() {
+            Some(parameters) => parameters,
+            None => return,
+        };
+
+        parameters
+            .iter_mut()
+            .par_bridge()
+            .for_each(|(key, value): (&str, &mut Value)| {
+                write_plugin_value(
+                    value,
+                    &format!("plugins.js:{name}:{key}"),
+                    romanize,
+                    &plugins_translation_map,
+                    &|string: &str| rules.skips_string(Section::Plugins, string),
+                );
+            });
     });
 
-    write(
-        output_path.join("plugins.js"),
+    write_file(
+        &output_path.join("plugins.js"),
         String::from("var $plugins =\n") + &to_string(&obj_arr).unwrap(),
-    )
-    .unwrap();
+    )?;
 
     if logging {
         println!("{file_written_msg} plugins.js");
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sonic_rs::from_str;
+
+    const SEQUENCE: &str = r#"[
+        {"code": 101, "indent": 0, "parameters": ["Actor1", 0, 0, 2]},
+        {"code": 401, "indent": 0, "parameters": ["First"]},
+        {"code": 401, "indent": 0, "parameters": ["Second"]},
+        {"code": 401, "indent": 0, "parameters": ["Third"]},
+        {"code": 0, "indent": 0, "parameters": []}
+    ]"#;
+
+    fn original_lines() -> Vec<String> {
+        vec!["First".into(), "Second".into(), "Third".into()]
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line: &&str| line.to_string()).collect()
+    }
+
+    fn texts(commands: &[Value]) -> Vec<(u64, &str)> {
+        commands
+            .iter()
+            .map(|command: &Value| {
+                (
+                    command["code"].as_u64().unwrap(),
+                    command["parameters"][0].as_str().unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sequence_grows() {
+        let mut list: Array = from_str(SEQUENCE).unwrap();
+        let mut removed: Vec<usize> = Vec::new();
+
+        let (index, inserted): (usize, Vec<Value>) = write_sequence(
+            &mut list,
+            &[1, 2, 3],
+            &original_lines(),
+            0,
+            401,
+            &lines(&["1", "2", "3", "4", "5"]),
+            None,
+            &mut removed,
+        );
+
+        assert_eq!(index, 3);
+        assert_eq!(texts(&list[1..4]), [(401, "1"), (401, "2"), (401, "3")]);
+        assert_eq!(texts(&inserted), [(401, "4"), (401, "5")]);
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn sequence_shrinks() {
+        let mut list: Array = from_str(SEQUENCE).unwrap();
+        let mut removed: Vec<usize> = Vec::new();
+
+        let (_, inserted): (usize, Vec<Value>) = write_sequence(
+            &mut list,
+            &[1, 2, 3],
+            &original_lines(),
+            0,
+            401,
+            &lines(&["1"]),
+            None,
+            &mut removed,
+        );
+
+        assert_eq!(list[1]["parameters"][0].as_str(), Some("1"));
+        assert!(inserted.is_empty());
+        assert_eq!(removed, [2, 3]);
+    }
+
+    #[test]
+    fn sequence_is_split_with_header() {
+        let mut list: Array = from_str(SEQUENCE).unwrap();
+        let mut removed: Vec<usize> = Vec::new();
+
+        let (index, inserted): (usize, Vec<Value>) = write_sequence(
+            &mut list,
+            &[1, 2, 3],
+            &original_lines(),
+            0,
+            401,
+            &lines(&["1", "2", "3", "4", "5"]),
+            Some(2),
+            &mut removed,
+        );
+
+        // Every message box after the first one starts with a copy of the header of the sequence
+        assert_eq!(index, 3);
+        assert_eq!(texts(&list[1..3]), [(401, "1"), (401, "2")]);
+        assert_eq!(
+            texts(&inserted),
+            [(101, "Actor1"), (401, "3"), (401, "4"), (101, "Actor1"), (401, "5")]
+        );
+        assert_eq!(removed, [3]);
+    }
+
+    #[test]
+    fn list_is_rebuilt_with_removed_and_inserted_commands() {
+        let mut list: Array = from_str(
+            r#"[
+                {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2]},
+                {"code": 401, "indent": 0, "parameters": ["A"]},
+                {"code": 401, "indent": 0, "parameters": ["B"]},
+                {"code": 401, "indent": 0, "parameters": ["C"]},
+                {"code": 101, "indent": 0, "parameters": ["", 0, 0, 2]},
+                {"code": 401, "indent": 0, "parameters": ["D"]},
+                {"code": 0, "indent": 0, "parameters": []}
+            ]"#,
+        )
+        .unwrap();
+        let map: HashMap<String, String, BuildHasherDefault<Xxh3>> = HashMap::from_iter([
+            ("A\nB\nC".to_string(), "a".to_string()),
+            ("D".to_string(), "d\ne".to_string()),
+        ]);
+
+        write_list(
+            &mut list,
+            &written_codes(&[101, 401]),
+            "Map001.json:1:0",
+            false,
+            Section::Maps,
+            &None,
+            &Rules::default(),
+            &map,
+            &HashMap::default(),
+            false,
+            "",
+            None,
+            0,
+        );
+
+        assert_eq!(
+            texts(&list),
+            [(101, ""), (401, "a"), (101, ""), (401, "d"), (401, "e"), (0, "")]
+        );
+    }
 }
@@ -0,0 +1,459 @@
+use crate::{
+    error::{parse_json, read_file, write_file, Error},
+    po::{parse_po, to_po_string, PoEntry},
+    section_names, Format, CONTEXT_SEPARATOR, SECTION_DIRS,
+};
+use roxmltree::{Document, Node};
+use std::{collections::HashMap, path::Path};
+
+struct Unit {
+    source: String,
+    target: String,
+    notes: Vec<(&'static str, String)>,
+}
+
+// Lines of .txt files use \# as a line break, while XLIFF uses regular line breaks
+fn escape(string: &str) -> String {
+    let mut escaped: String = String::with_capacity(string.len());
+
+    for char in string.replace(r"\#", "\n").chars() {
+        match char {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Literal carriage returns are normalized to line feeds by XML parsers
+            '\r' => escaped.push_str("&#13;"),
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+// XML doesn't allow control characters other than tab and line breaks, even as character references
+fn find_control_char(string: &str) -> Option<char> {
+    string
+        .chars()
+        .find(|&char: &char| char < ' ' && !matches!(char, '\t' | '\n' | '\r'))
+}
+
+// Returns the text of the element, including the text of its inline elements
+fn element_text(node: Node) -> String {
+    node.descendants()
+        .filter(|node: &Node| node.is_text())
+        .filter_map(|node: Node| node.text())
+        .collect::<String>()
+        .replace('\n', r"\#")
+}
+
+/// Reads units of the section from its translation files. Units of .txt files are located by the line number and by
+/// the locations from .locations.json file, if it was written, while units of .po files carry the references of the
+/// entry.
+fn read_units(path: &Path, dir: &str, name: &str, format: Format) -> Result<Vec<Unit>, Error> {
+    Ok(match format {
+        Format::Txt => {
+            let original_content: String = read_file(&path.join(format!("{name}.txt")))?;
+            let translated_content: String = read_file(&path.join(format!("{name}_trans.txt")))?;
+
+            let locations_file_path: &Path = &path.join(format!("{name}.locations.json"));
+            let mut locations: HashMap<String, Vec<String>> = if locations_file_path.exists() {
+                parse_json(locations_file_path, &read_file(locations_file_path)?)?
+            } else {
+                HashMap::new()
+            };
+
+            original_content
+                .split('\n')
+                .zip(translated_content.split('\n'))
+                .enumerate()
+                .map(|(i, (original, translated))| {
+                    let mut notes: Vec<(&'static str, String)> = vec![("line", format!("{dir}/{name}.txt:{}", i + 1))];
+
+                    if let Some(line_locations) = locations.remove(original) {
+                        notes.extend(
+                            line_locations
+                                .into_iter()
+                                .map(|location: String| ("location", location)),
+                        );
+                    }
+
+                    // Contextual keys are split to the location and the text, like contexts of .po entries
+                    let source: &str = match original.split_once(CONTEXT_SEPARATOR) {
+                        Some((location, text)) => {
+                            notes.push(("context", location.to_string()));
+                            text
+                        }
+                        None => original,
+                    };
+
+                    Unit {
+                        source: source.to_string(),
+                        target: translated.to_string(),
+                        notes,
+                    }
+                })
+                .collect()
+        }
+        Format::Po => parse_po(&read_file(&path.join(format!("{name}.po")))?)
+            .into_iter()
+            .map(|entry: PoEntry| {
+                let mut notes: Vec<(&'static str, String)> = entry
+                    .references
+                    .into_iter()
+                    .map(|reference: String| ("location", reference))
+                    .collect();
+
+                if let Some(context) = entry.context {
+                    notes.push(("context", context));
+                }
+
+                notes.extend(entry.comments.into_iter().map(|comment: String| ("comment", comment)));
+
+                Unit {
+                    source: entry.original,
+                    target: entry.translated,
+                    notes,
+                }
+            })
+            .collect(),
+    })
+}
+
+/// Exports translation files of maps, other and plugins folders to a single XLIFF 2.0 file, with a `<file>` per every
+/// translation file and a `<unit>` per every line of it. Returns the error of the file, that couldn't be read or written,
+/// or that contains control characters, which can't be written to XML.
+/// # Parameters
+/// * `translation_path` - path to the translation directory
+/// * `xliff_file_path` - path to the XLIFF file to create
+/// * `source_language` - language of the original game text
+/// * `target_language` - language of the translation
+/// * `logging` - whether to log or not
+/// * `file_exported_msg` - message to log when file is exported
+/// * `format` - format of translation files
+pub fn export_xliff(
+    translation_path: &Path,
+    xliff_file_path: &Path,
+    source_language: &str,
+    target_language: &str,
+    logging: bool,
+    file_exported_msg: &str,
+    format: Format,
+) -> Result<(), Error> {
+    let mut content: String = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="{}" trgLang="{}">
+"#,
+        escape(source_language),
+        escape(target_language)
+    );
+
+    let extension: &str = match format {
+        Format::Txt => "txt",
+        Format::Po => "po",
+    };
+
+    for dir in SECTION_DIRS {
+        let path: &Path = &translation_path.join(dir);
+
+        if !path.exists() {
+            continue;
+        }
+
+        for name in section_names(path, format)? {
+            // Ids can't contain spaces and most of the symbols, and files are imported by their original path anyway
+            let id: String = format!("{dir}.{name}")
+                .chars()
+                .map(|char: char| {
+                    if char.is_alphanumeric() || matches!(char, '.' | '-' | '_') {
+                        char
+                    } else {
+                        '_'
+                    }
+                })
+                .collect();
+
+            content.push_str(&format!(
+                "  <file id=\"{}\" original=\"{}\" xml:space=\"preserve\">\n",
+                escape(&id),
+                escape(&format!("{dir}/{name}.{extension}"))
+            ));
+
+            for (i, unit) in read_units(path, dir, &name, format)?.into_iter().enumerate() {
+                if let Some(char) = [&unit.source, &unit.target]
+                    .into_iter()
+                    .chain(unit.notes.iter().map(|(_, note): &(&'static str, String)| note))
+                    .find_map(|string: &String| find_control_char(string))
+                {
+                    return Err(Error::InvalidFile(
+                        path.join(format!("{name}.{extension}")),
+                        format!(
+                            "unit {} contains control character U+{:04X}, which XML doesn't allow",
+                            i + 1,
+                            char as u32
+                        ),
+                    ));
+                }
+
+                content.push_str(&format!("    <unit id=\"{}\">\n", i + 1));
+
+                if !unit.notes.is_empty() {
+                    content.push_str("      <notes>\n");
+
+                    for (category, note) in unit.notes {
+                        content.push_str(&format!(
+                            "        <note category=\"{category}\">{}</note>\n",
+                            escape(&note)
+                        ));
+                    }
+
+                    content.push_str("      </notes>\n");
+                }
+
+                if unit.target.is_empty() {
+                    content.push_str(&format!(
+                        "      <segment state=\"initial\">\n        <source>{}</source>\n      </segment>\n",
+                        escape(&unit.source)
+                    ));
+                } else {
+                    content.push_str(&format!(
+                        "      <segment state=\"translated\">\n        <source>{}</source>\n        <target>{}</target>\n      </segment>\n",
+                        escape(&unit.source),
+                        escape(&unit.target)
+                    ));
+                }
+
+                content.push_str("    </unit>\n");
+            }
+
+            content.push_str("  </file>\n");
+
+            if logging {
+                println!("{file_exported_msg} {dir}/{name}.{extension}");
+            }
+        }
+    }
+
+    content.push_str("</xliff>\n");
+    write_file(xliff_file_path, content)
+}
+
+/// Imports translation from XLIFF 2.0 file, previously created with export_xliff, back to the translation files. Units
+/// are matched with the lines of the files by their ids, and units, which source doesn't match the line, are skipped.
+/// Returns the error of the XLIFF file, that isn't valid or refers to a file outside of the translation folders, or of
+/// the file, that couldn't be read or written.
+/// # Parameters
+/// * `xliff_file_path` - path to the XLIFF file
+/// * `translation_path` - path to the translation directory
+/// * `logging` - whether to log or not
+/// * `file_written_msg` - message to log when file is written
+pub fn import_xliff(
+    xliff_file_path: &Path,
+    translation_path: &Path,
+    logging: bool,
+    file_written_msg: &str,
+) -> Result<(), Error> {
+    let content: String = read_file(xliff_file_path)?;
+    let document: Document = Document::parse(&content)
+        .map_err(|error: roxmltree::Error| Error::Xml(xliff_file_path.to_path_buf(), error))?;
+
+    for file in document
+        .root_element()
+        .children()
+        .filter(|node: &Node| node.has_tag_name("file"))
+    {
+        let original: &str = file.attribute("original").ok_or_else(|| {
+            Error::InvalidFile(
+                xliff_file_path.to_path_buf(),
+                "`file` element doesn't have `original` attribute".to_string(),
+            )
+        })?;
+
+        let (section, format): (&str, Format) = if let Some(section) = original.strip_suffix(".txt") {
+            (section, Format::Txt)
+        } else if let Some(section) = original.strip_suffix(".po") {
+            (section, Format::Po)
+        } else {
+            continue;
+        };
+
+        // The attribute comes from an untrusted file, so only the files of the translation folders can be written
+        let is_section_path: bool = section.split_once('/').is_some_and(|(dir, name): (&str, &str)| {
+            SECTION_DIRS.contains(&dir) && !matches!(name, "" | "." | "..") && !name.contains(['/', '\\'])
+        });
+
+        if !is_section_path {
+            return Err(Error::InvalidFile(
+                xliff_file_path.to_path_buf(),
+                format!("`original` attribute `{original}` doesn't refer to a file of the translation folders"),
+            ));
+        }
+
+        let units: Vec<(usize, String, String)> = file
+            .descendants()
+            .filter(|node: &Node| node.has_tag_name("unit"))
+            .filter_map(|unit: Node| {
+                let index: usize = unit.attribute("id")?.parse::<usize>().ok()?.checked_sub(1)?;
+                let source: Node = unit.descendants().find(|node: &Node| node.has_tag_name("source"))?;
+                let target: Node = unit.descendants().find(|node: &Node| node.has_tag_name("target"))?;
+
+                // Contextual keys of .txt files are exported split to the location in the context note and the text
+                let context: Option<Node> = unit
+                    .descendants()
+                    .find(|node: &Node| node.has_tag_name("note") && node.attribute("category") == Some("context"));
+
+                let source: String = match (format, context) {
+                    (Format::Txt, Some(context)) => {
+                        format!("{}{CONTEXT_SEPARATOR}{}", element_text(context), element_text(source))
+                    }
+                    _ => element_text(source),
+                };
+
+                Some((index, source, element_text(target)))
+            })
+            .collect();
+
+        match format {
+            Format::Txt => {
+                let original_path: &Path = &translation_path.join(format!("{section}.txt"));
+                let translated_path: &Path = &translation_path.join(format!("{section}_trans.txt"));
+
+                if !original_path.exists() || !translated_path.exists() {
+                    continue;
+                }
+
+                let original_content: String = read_file(original_path)?;
+                let original_lines: Vec<&str> = original_content.split('\n').collect();
+
+                let translated_content: String = read_file(translated_path)?;
+                let mut translated_lines: Vec<String> = translated_content.split('\n').map(str::to_string).collect();
+                translated_lines.resize(original_lines.len(), String::new());
+
+                for (index, source, target) in units {
+                    if original_lines.get(index) == Some(&source.as_str()) {
+                        translated_lines[index] = target;
+                    }
+                }
+
+                write_file(translated_path, translated_lines.join("\n"))?;
+            }
+            Format::Po => {
+                let po_path: &Path = &translation_path.join(format!("{section}.po"));
+
+                if !po_path.exists() {
+                    continue;
+                }
+
+                let mut entries: Vec<PoEntry> = parse_po(&read_file(po_path)?);
+
+                for (index, source, target) in units {
+                    if let Some(entry) = entries.get_mut(index) {
+                        if entry.original == source {
+                            entry.translated = target;
+                        }
+                    }
+                }
+
+                write_file(po_path, to_po_string(&entries))?;
+            }
+        }
+
+        if logging {
+            println!("{file_written_msg} {original}");
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        env::temp_dir,
+        fs::{create_dir_all, remove_dir_all},
+        path::PathBuf,
+    };
+
+    fn translation_dir(name: &str) -> PathBuf {
+        let path: PathBuf = temp_dir().join(format!("rvpacker-xliff-{name}-{}", std::process::id()));
+        create_dir_all(path.join("other")).unwrap();
+        path
+    }
+
+    #[test]
+    fn contextual_keys_are_exported_with_context_notes() {
+        let path: PathBuf = translation_dir("context");
+        let xliff_file_path: PathBuf = path.join("translation.xlf");
+
+        write_file(
+            &path.join("other/items.txt"),
+            format!("Potion\nItems.json:1:name{CONTEXT_SEPARATOR}Potion"),
+        )
+        .unwrap();
+        write_file(&path.join("other/items_trans.txt"), "Зелье\n").unwrap();
+
+        export_xliff(&path, &xliff_file_path, "en", "ru", false, "", Format::Txt).unwrap();
+        let content: String = read_file(&xliff_file_path).unwrap();
+
+        assert!(!content.contains(CONTEXT_SEPARATOR));
+        assert!(content.contains(r#"<note category="context">Items.json:1:name</note>"#));
+        assert!(content.contains(r#"trgLang="ru""#));
+
+        write_file(
+            &xliff_file_path,
+            content.replace(
+                "<segment state=\"initial\">\n        <source>Potion</source>\n",
+                "<segment state=\"translated\">\n        <source>Potion</source>\n        <target>Эликсир</target>\n",
+            ),
+        )
+        .unwrap();
+        import_xliff(&xliff_file_path, &path, false, "").unwrap();
+        let translation: String = read_file(&path.join("other/items_trans.txt")).unwrap();
+        remove_dir_all(&path).unwrap();
+
+        assert_eq!(translation, "Зелье\nЭликсир");
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        let path: PathBuf = translation_dir("control");
+
+        write_file(&path.join("other/items.txt"), "Potion\u{1}").unwrap();
+        write_file(&path.join("other/items_trans.txt"), "").unwrap();
+
+        let result: Result<(), Error> =
+            export_xliff(&path, &path.join("translation.xlf"), "en", "ru", false, "", Format::Txt);
+        remove_dir_all(&path).unwrap();
+
+        assert!(matches!(result, Err(Error::InvalidFile(..))));
+    }
+
+    #[test]
+    fn files_outside_of_translation_folders_are_rejected() {
+        let path: PathBuf = translation_dir("original");
+        let xliff_file_path: PathBuf = path.join("translation.xlf");
+
+        for original in [
+            "other/../../items.txt",
+            "/tmp/items.txt",
+            "scripts/items.txt",
+            "other/a\\b.txt",
+        ] {
+            write_file(
+                &xliff_file_path,
+                format!(
+                    r#"<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="ru"><file id="f" original="{original}"/></xliff>"#
+                ),
+            )
+            .unwrap();
+
+            assert!(matches!(
+                import_xliff(&xliff_file_path, &path, false, ""),
+                Err(Error::InvalidFile(..))
+            ));
+        }
+
+        remove_dir_all(&path).unwrap();
+    }
+}